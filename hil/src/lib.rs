@@ -0,0 +1,92 @@
+//! Adapters running the HTS221 driver on an embassy HAL, for the on-target tests.
+//!
+//! The driver is tested through its own [`I2cInterface`](hts221_async::I2cInterface), so only
+//! the bus is adapted here: current HALs implement embedded-hal-async 1.0, the driver the 0.2
+//! alpha.
+#![no_std]
+
+use embassy_time::Timer;
+use embedded_hal_async::i2c as bus;
+use embedded_hal_async_0_2::{
+    delay::DelayUs,
+    i2c::{Error, ErrorKind, ErrorType, I2c, NoAcknowledgeSource, Operation, SevenBitAddress},
+};
+
+/// Adapter running an embedded-hal-async 1.0 I2C bus behind the trait of the driver.
+pub struct Bus<I>(pub I);
+
+/// Error of an embedded-hal-async 1.0 I2C bus, classified for the driver.
+#[derive(Debug)]
+pub struct BusError<E>(pub E);
+
+impl<E: bus::Error> Error for BusError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self.0.kind() {
+            bus::ErrorKind::Bus => ErrorKind::Bus,
+            bus::ErrorKind::ArbitrationLoss => ErrorKind::ArbitrationLoss,
+            bus::ErrorKind::NoAcknowledge(source) => ErrorKind::NoAcknowledge(match source {
+                bus::NoAcknowledgeSource::Address => NoAcknowledgeSource::Address,
+                bus::NoAcknowledgeSource::Data => NoAcknowledgeSource::Data,
+                bus::NoAcknowledgeSource::Unknown => NoAcknowledgeSource::Unknown,
+            }),
+            bus::ErrorKind::Overrun => ErrorKind::Overrun,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl<I: bus::I2c> ErrorType for Bus<I> {
+    type Error = BusError<I::Error>;
+}
+
+impl<I: bus::I2c> I2c<SevenBitAddress> for Bus<I> {
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(address, read).await.map_err(BusError)
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, write).await.map_err(BusError)
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.0
+            .write_read(address, write, read)
+            .await
+            .map_err(BusError)
+    }
+
+    // Operations are run one after the other, not as a single transaction. The driver only
+    // uses write and write_read, which keep their repeated start.
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Read(read) => self.0.read(address, read).await,
+                Operation::Write(write) => self.0.write(address, write).await,
+            }
+            .map_err(BusError)?;
+        }
+        Ok(())
+    }
+}
+
+/// Delay waiting on the embassy timer.
+pub struct Delay;
+
+impl DelayUs for Delay {
+    async fn delay_us(&mut self, us: u32) {
+        Timer::after_micros(us as u64).await
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        Timer::after_millis(ms as u64).await
+    }
+}
@@ -0,0 +1,106 @@
+//! Driver tests against the HTS221 of a B-L475E-IOT01A discovery kit.
+//!
+//! Every test starts from a reset of the board, with the sensor on I2C2 (PB10, PB11).
+#![no_std]
+#![no_main]
+
+use defmt_rtt as _;
+use panic_probe as _;
+
+#[embedded_test::tests]
+mod tests {
+    use embassy_stm32::{
+        bind_interrupts,
+        i2c::{self, I2c},
+        peripherals::{self, DMA1_CH4, DMA1_CH5, I2C2},
+        time::Hertz,
+    };
+    use hts221_async::{
+        Config, Hts221, I2cInterface, OutputDataRate, OPERATING_TEMPERATURE_MAX,
+        OPERATING_TEMPERATURE_MIN,
+    };
+    use hts221_hil::{Bus, Delay};
+
+    bind_interrupts!(struct Irqs {
+        I2C2_EV => i2c::EventInterruptHandler<peripherals::I2C2>;
+        I2C2_ER => i2c::ErrorInterruptHandler<peripherals::I2C2>;
+    });
+
+    type Driver = Hts221<I2cInterface<Bus<I2c<'static, I2C2, DMA1_CH4, DMA1_CH5>>>>;
+
+    // Heater pulse long enough to measurably warm the sensor
+    const HEATER_PULSE_MS: u32 = 3_000;
+
+    #[init]
+    fn init() -> Driver {
+        let p = embassy_stm32::init(Default::default());
+        let i2c = I2c::new(
+            p.I2C2,
+            p.PB10,
+            p.PB11,
+            Irqs,
+            p.DMA1_CH4,
+            p.DMA1_CH5,
+            Hertz(100_000),
+            Default::default(),
+        );
+        Hts221::new(Bus(i2c))
+    }
+
+    #[test]
+    async fn health(mut hts221: Driver) {
+        let report = hts221.health().await.unwrap();
+        defmt::assert!(report.sensor_valid);
+        defmt::assert!(report.calibration_valid);
+    }
+
+    #[test]
+    async fn read(mut hts221: Driver) {
+        hts221.initialize().await.unwrap();
+        let acquisition = hts221.read().await.unwrap();
+        defmt::assert!((OPERATING_TEMPERATURE_MIN..=OPERATING_TEMPERATURE_MAX)
+            .contains(&acquisition.temperature.raw_value()));
+        defmt::assert!((0.0..=100.0).contains(&acquisition.relative_humidity));
+    }
+
+    #[test]
+    async fn one_shot(mut hts221: Driver) {
+        hts221.initialize().await.unwrap();
+        let continuous = hts221.read().await.unwrap();
+        let config = hts221.config();
+        hts221
+            .reconfigure(Config {
+                output_data_rate: OutputDataRate::OneShot,
+                ..config
+            })
+            .await
+            .unwrap();
+        let mut samples = [continuous; 2];
+        hts221.read_into(&mut samples, &mut Delay).await.unwrap();
+        for sample in samples {
+            defmt::assert!(sample.approx_eq(&continuous, 2.0, 5.0));
+        }
+    }
+
+    #[test]
+    async fn heater(mut hts221: Driver) {
+        hts221.initialize().await.unwrap();
+        let before = hts221.read().await.unwrap();
+        hts221
+            .heater_pulse(&mut Delay, HEATER_PULSE_MS)
+            .await
+            .unwrap();
+        let event = hts221.read_qualified().await.unwrap();
+        defmt::assert!(event.quality.heater_recently_on);
+        defmt::assert!(event.acquisition.temperature.raw_value() > before.temperature.raw_value());
+    }
+
+    #[test]
+    async fn config_round_trip(mut hts221: Driver) {
+        hts221.set_verify_config(true);
+        hts221.initialize().await.unwrap();
+        let snapshot = hts221.save_config().await.unwrap();
+        hts221.restore_config(&snapshot).await.unwrap();
+        defmt::assert_eq!(hts221.save_config().await.unwrap(), snapshot);
+    }
+}
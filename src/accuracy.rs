@@ -0,0 +1,137 @@
+use super::{
+    register::av_conf::AvConf, Celsius, Hts221, Hts221Error, Interface, SensorAcquisition,
+};
+
+/// Temperature accuracy in degrees celsius, between 15 and 40 °C.
+pub const TEMPERATURE_ACCURACY: f32 = 0.5;
+
+/// Temperature accuracy in degrees celsius, between 0 and 60 °C.
+pub const TEMPERATURE_ACCURACY_EXTENDED: f32 = 1.0;
+
+/// Relative humidity accuracy in percentage points, between 20 and 80 %RH.
+pub const HUMIDITY_ACCURACY: f32 = 3.5;
+
+/// Relative humidity accuracy in percentage points, between 0 and 100 %RH.
+pub const HUMIDITY_ACCURACY_EXTENDED: f32 = 5.0;
+
+// Temperature noise in degrees celsius RMS, for each AVGT setting
+const TEMPERATURE_NOISE: [f32; 8] = [0.08, 0.05, 0.04, 0.03, 0.02, 0.015, 0.01, 0.007];
+
+// Relative humidity noise in percentage points RMS, for each AVGH setting
+const HUMIDITY_NOISE: [f32; 8] = [0.4, 0.3, 0.2, 0.15, 0.1, 0.07, 0.05, 0.03];
+
+// Assumed temperature accuracy outside of the range specified by the datasheet
+const TEMPERATURE_ACCURACY_UNSPECIFIED: f32 = 2.0;
+
+/// Estimated uncertainty of an acquisition.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AccuracyEstimate {
+    /// Temperature uncertainty in degrees celsius.
+    pub temperature: f32,
+    /// Relative humidity uncertainty in percentage points.
+    pub relative_humidity: f32,
+}
+
+impl AccuracyEstimate {
+    /// Estimate the uncertainty of an acquisition from the datasheet accuracy at its operating
+    /// point and the noise of the given averaging configuration.
+    ///
+    /// Accuracy and noise are added linearly, giving a conservative bound.
+    pub fn new(acquisition: &SensorAcquisition<Celsius>, av_conf: AvConf) -> Self {
+        let t = acquisition.temperature.raw_value();
+        let temperature = if (15.0..=40.0).contains(&t) {
+            TEMPERATURE_ACCURACY
+        } else if (0.0..=60.0).contains(&t) {
+            TEMPERATURE_ACCURACY_EXTENDED
+        } else {
+            TEMPERATURE_ACCURACY_UNSPECIFIED
+        };
+
+        let rh = acquisition.relative_humidity;
+        let relative_humidity = if (20.0..=80.0).contains(&rh) {
+            HUMIDITY_ACCURACY
+        } else {
+            HUMIDITY_ACCURACY_EXTENDED
+        };
+
+        Self {
+            temperature: temperature
+                + TEMPERATURE_NOISE[(av_conf.temperature_average() & 0b111) as usize],
+            relative_humidity: relative_humidity
+                + HUMIDITY_NOISE[(av_conf.humidity_average() & 0b111) as usize],
+        }
+    }
+}
+
+impl<I> Hts221<I>
+where
+    I: Interface,
+{
+    /// Estimate the uncertainty of an acquisition, reading back the averaging configuration
+    /// of the device.
+    pub async fn accuracy_estimate(
+        &mut self,
+        acquisition: &SensorAcquisition<Celsius>,
+    ) -> Result<AccuracyEstimate, Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = AvConf::read(&mut self.interface)
+            .await
+            .map(|av_conf| AccuracyEstimate::new(acquisition, av_conf))
+            .map_err(Hts221Error::I2c);
+        self.track(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn estimate(celsius: f32, relative_humidity: f32, av_conf: u8) -> AccuracyEstimate {
+        let mut hts221 = initialized();
+        sensor(&mut hts221).regs[AV_CONF as usize] = av_conf;
+        let acquisition = SensorAcquisition {
+            temperature: celsius.into(),
+            relative_humidity,
+        };
+        block_on(hts221.accuracy_estimate(&acquisition)).unwrap()
+    }
+
+    fn assert_estimate(estimate: AccuracyEstimate, temperature: f32, relative_humidity: f32) {
+        assert!((estimate.temperature - temperature).abs() < 1e-6);
+        assert!((estimate.relative_humidity - relative_humidity).abs() < 1e-6);
+    }
+
+    #[test]
+    fn datasheet_bands_with_default_averaging() {
+        // AVGT of 16 and AVGH of 32 samples
+        assert_estimate(estimate(25.0, 50.0, 0x1B), 0.53, 3.65);
+        assert_estimate(estimate(50.0, 90.0, 0x1B), 1.03, 5.15);
+        assert_estimate(estimate(-10.0, 10.0, 0x1B), 2.03, 5.15);
+    }
+
+    #[test]
+    fn datasheet_bands_with_extreme_averaging() {
+        // Fewest samples
+        assert_estimate(estimate(40.0, 80.0, 0x00), 0.58, 3.9);
+        // Most samples
+        assert_estimate(estimate(0.0, 20.0, 0x3F), 1.007, 3.53);
+        assert_estimate(estimate(60.5, 100.0, 0x3F), 2.007, 5.03);
+    }
+
+    #[test]
+    fn ends_cancelled_pulse() {
+        let mut hts221 = initialized();
+        let mut stall = Stall;
+        let mut pulse = Box::pin(hts221.heater_pulse(&mut stall, 1000));
+        assert!(poll_once(&mut pulse).is_pending());
+        drop(pulse);
+        let acquisition = SensorAcquisition {
+            temperature: 25.0.into(),
+            relative_humidity: 50.0,
+        };
+        block_on(hts221.accuracy_estimate(&acquisition)).unwrap();
+        assert!(!sensor(&mut hts221).heater_on());
+    }
+}
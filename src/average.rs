@@ -0,0 +1,115 @@
+use super::{Celsius, SensorAcquisition};
+
+/// Time-weighted average of acquisitions over a window.
+///
+/// Every acquisition is weighted by how long it was valid, so irregular or duty-cycled
+/// sampling still yields the correct average, unlike the arithmetic mean of the samples.
+pub struct TimeWeightedAverage {
+    temperature: f64,
+    relative_humidity: f64,
+    total_ms: u64,
+    last: Option<(SensorAcquisition<Celsius>, u64)>,
+}
+
+impl TimeWeightedAverage {
+    /// Create an empty aggregator.
+    pub fn new() -> Self {
+        Self {
+            temperature: 0.0,
+            relative_humidity: 0.0,
+            total_ms: 0,
+            last: None,
+        }
+    }
+
+    /// Add an acquisition which was valid for `duration_ms`.
+    pub fn add(&mut self, acquisition: &SensorAcquisition<Celsius>, duration_ms: u32) {
+        let weight = duration_ms as f64;
+        self.temperature += acquisition.temperature.value as f64 * weight;
+        self.relative_humidity += acquisition.relative_humidity as f64 * weight;
+        self.total_ms = self.total_ms.saturating_add(duration_ms as u64);
+    }
+
+    /// Add an acquisition taken at `timestamp_ms` of a monotonic time source.
+    ///
+    /// The previous acquisition is weighted by the time elapsed until this one; the latest
+    /// acquisition only counts once the next one arrives.
+    pub fn update(&mut self, acquisition: SensorAcquisition<Celsius>, timestamp_ms: u64) {
+        if let Some((previous, since)) = self.last.take() {
+            let elapsed = timestamp_ms.saturating_sub(since);
+            self.add(&previous, elapsed.min(u32::MAX as u64) as u32);
+        }
+        self.last.replace((acquisition, timestamp_ms));
+    }
+
+    /// Total time covered by the average, in milliseconds.
+    pub fn total_ms(&self) -> u64 {
+        self.total_ms
+    }
+
+    /// The average over the window, or `None` if no time has been covered yet.
+    pub fn average(&self) -> Option<SensorAcquisition<Celsius>> {
+        if self.total_ms == 0 {
+            return None;
+        }
+        let total = self.total_ms as f64;
+        Some(SensorAcquisition {
+            temperature: ((self.temperature / total) as f32).into(),
+            relative_humidity: (self.relative_humidity / total) as f32,
+        })
+    }
+
+    /// Start a new window. The latest acquisition passed to [`update`](Self::update) is kept,
+    /// so it is weighted into the new window.
+    pub fn reset(&mut self) {
+        self.temperature = 0.0;
+        self.relative_humidity = 0.0;
+        self.total_ms = 0;
+    }
+}
+
+impl Default for TimeWeightedAverage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acquisition(temperature: f32, relative_humidity: f32) -> SensorAcquisition<Celsius> {
+        SensorAcquisition {
+            temperature: temperature.into(),
+            relative_humidity,
+        }
+    }
+
+    #[test]
+    fn weights_by_duration() {
+        let mut average = TimeWeightedAverage::new();
+        assert!(average.average().is_none());
+        average.add(&acquisition(20.0, 40.0), 3000);
+        average.add(&acquisition(24.0, 60.0), 1000);
+        let result = average.average().unwrap();
+        assert_eq!(result.temperature.value, 21.0);
+        assert_eq!(result.relative_humidity, 45.0);
+        assert_eq!(average.total_ms(), 4000);
+    }
+
+    #[test]
+    fn weights_previous_acquisition_until_the_next() {
+        let mut average = TimeWeightedAverage::new();
+        average.update(acquisition(20.0, 40.0), 1000);
+        assert!(average.average().is_none());
+        average.update(acquisition(30.0, 80.0), 2000);
+        average.update(acquisition(0.0, 0.0), 5000);
+        assert_eq!(average.average().unwrap().temperature.value, 27.5);
+
+        // The latest acquisition carries over into the next window
+        average.reset();
+        average.update(acquisition(10.0, 20.0), 6000);
+        assert_eq!(average.average().unwrap().temperature.value, 0.0);
+        assert_eq!(average.total_ms(), 1000);
+    }
+}
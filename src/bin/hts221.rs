@@ -0,0 +1,213 @@
+//! Probe, initialize and read an HTS221 on a Linux I2C bus.
+
+use embedded_hal_0_2::blocking::i2c::{Read, Write, WriteRead};
+use embedded_hal_async::i2c::{Error, ErrorKind, ErrorType, I2c, Operation, SevenBitAddress};
+use futures::executor::block_on;
+use hts221_async::{Hts221, Hts221Error, I2cInterface};
+use linux_embedded_hal::I2cdev;
+use std::{fmt::Debug, process::exit, thread::sleep, time::Duration};
+
+const USAGE: &str = "usage: hts221 [--bus /dev/i2c-N] <probe | read [COUNT] | dump>";
+
+// Registers worth inspecting when triaging a sensor, with their names from the datasheet
+const REGISTERS: &[(u8, &str)] = &[
+    (0x0F, "WHO_AM_I"),
+    (0x10, "AV_CONF"),
+    (0x20, "CTRL_REG1"),
+    (0x21, "CTRL_REG2"),
+    (0x22, "CTRL_REG3"),
+    (0x27, "STATUS_REG"),
+    (0x28, "HUMIDITY_OUT_L"),
+    (0x29, "HUMIDITY_OUT_H"),
+    (0x2A, "TEMP_OUT_L"),
+    (0x2B, "TEMP_OUT_H"),
+];
+
+const CALIBRATION: u8 = 0x30;
+
+enum Command {
+    Probe,
+    Read(u32),
+    Dump,
+}
+
+/// Adapter running a blocking embedded-hal 0.2 I2C implementation behind the async trait.
+struct Blocking<T>(T);
+
+/// Error of a blocking I2C implementation, which embedded-hal 0.2 does not classify.
+#[derive(Debug)]
+struct BusError<E>(E);
+
+impl<E: Debug> Error for BusError<E> {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+impl<T, E> ErrorType for Blocking<T>
+where
+    T: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    E: Debug,
+{
+    type Error = BusError<E>;
+}
+
+impl<T, E> I2c<SevenBitAddress> for Blocking<T>
+where
+    T: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    E: Debug,
+{
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.0.read(address, read).map_err(BusError)
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, write).map_err(BusError)
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.0.write_read(address, write, read).map_err(BusError)
+    }
+
+    // Operations are run one after the other, not as a single transaction
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations {
+            match operation {
+                Operation::Read(read) => self.0.read(address, read),
+                Operation::Write(write) => self.0.write(address, write),
+            }
+            .map_err(BusError)?;
+        }
+        Ok(())
+    }
+}
+
+type Driver = Hts221<I2cInterface<Blocking<I2cdev>>>;
+type DriverError = Hts221Error<<Blocking<I2cdev> as ErrorType>::Error>;
+
+fn main() {
+    let mut bus = String::from("/dev/i2c-1");
+    let mut command = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bus" => bus = args.next().unwrap_or_else(|| usage()),
+            "probe" => command = Some(Command::Probe),
+            "dump" => command = Some(Command::Dump),
+            "read" => {
+                let count = match args.next() {
+                    Some(count) => count.parse().unwrap_or_else(|_| usage()),
+                    None => 1,
+                };
+                command = Some(Command::Read(count));
+            }
+            _ => usage(),
+        }
+    }
+    let command = command.unwrap_or_else(|| usage());
+
+    let i2c = I2cdev::new(&bus).unwrap_or_else(|e| {
+        eprintln!("unable to open {}: {}", bus, e);
+        exit(1);
+    });
+    let mut hts221 = Hts221::new(Blocking(i2c));
+
+    let result = block_on(async {
+        match command {
+            Command::Probe => probe(&mut hts221).await,
+            Command::Read(count) => read(&mut hts221, count).await,
+            Command::Dump => dump(&mut hts221).await,
+        }
+    });
+
+    if let Err(e) = result {
+        eprintln!("error: {:?}", e);
+        exit(1);
+    }
+}
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    exit(2);
+}
+
+async fn probe(hts221: &mut Driver) -> Result<(), DriverError> {
+    let report = hts221.health().await?;
+    println!(
+        "WHO_AM_I:    {:#04x} ({})",
+        report.who_am_i,
+        if report.sensor_valid {
+            "HTS221"
+        } else {
+            "unexpected"
+        }
+    );
+    println!(
+        "calibration: {}",
+        if report.calibration_valid {
+            "valid"
+        } else {
+            "invalid"
+        }
+    );
+    println!(
+        "status:      temperature {}, humidity {}",
+        available(report.temperature_available),
+        available(report.humidity_available)
+    );
+    Ok(())
+}
+
+async fn read(hts221: &mut Driver, count: u32) -> Result<(), DriverError> {
+    hts221.initialize().await?;
+    for i in 0..count {
+        if i > 0 {
+            sleep(Duration::from_secs(1));
+        }
+        let acquisition = hts221.read().await?;
+        println!(
+            "temperature: {:.2}°C, relative humidity: {:.1}%",
+            acquisition.temperature.raw_value(),
+            acquisition.relative_humidity
+        );
+    }
+    Ok(())
+}
+
+async fn dump(hts221: &mut Driver) -> Result<(), DriverError> {
+    for (register, name) in REGISTERS {
+        let mut buf = [0; 1];
+        hts221.read_regs(*register, &mut buf).await?;
+        println!(
+            "{:#04x} {:<16} {:#04x} {:#010b}",
+            register, name, buf[0], buf[0]
+        );
+    }
+
+    let mut buf = [0; 16];
+    hts221.read_regs(CALIBRATION, &mut buf).await?;
+    print!("{:#04x} {:<16}", CALIBRATION, "CALIB_0..F");
+    for byte in buf {
+        print!(" {:02x}", byte);
+    }
+    println!();
+    Ok(())
+}
+
+fn available(available: bool) -> &'static str {
+    if available {
+        "available"
+    } else {
+        "not available"
+    }
+}
@@ -0,0 +1,392 @@
+use super::{
+    register::{
+        av_conf::{AvConf, AV_CONF},
+        calibration::Calibration,
+        ctrl1::{BlockDataUpdate, Ctrl1, CTRL_REG1},
+        ctrl2::Ctrl2,
+        ctrl3::{Ctrl3, CTRL_REG3},
+    },
+    ActiveState, DataReadyConfig, Hts221, Hts221Error, I2cInterface, Interface, OutputDataRate,
+    ReadyMode,
+};
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+
+// Bits of the configuration registers which are not reserved
+const CTRL_REG1_BITS: u8 = 0b1000_0111;
+const CTRL_REG3_BITS: u8 = 0b1100_0100;
+const AV_CONF_BITS: u8 = 0b0011_1111;
+
+/// Device configuration applied by the driver.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Config {
+    /// Rate at which new samples are produced.
+    pub output_data_rate: OutputDataRate,
+    /// Whether the output registers are only updated once both bytes have been read.
+    pub block_data_update: bool,
+    /// Number of internal samples averaged for each output.
+    pub averaging: AvConf,
+    /// Configuration of the data ready signal.
+    pub data_ready: DataReadyConfig,
+}
+
+impl Default for Config {
+    /// Sampling at 1 Hz with the averaging reset values and the data ready signal enabled.
+    fn default() -> Self {
+        Self {
+            output_data_rate: OutputDataRate::Hz1,
+            block_data_update: true,
+            // Reset value: 16 temperature and 32 humidity samples
+            averaging: 0x1B.into(),
+            data_ready: DataReadyConfig {
+                enabled: true,
+                active_state: ActiveState::High,
+                mode: ReadyMode::PushPull,
+            },
+        }
+    }
+}
+
+/// Snapshot of the control and averaging registers of the device.
+///
+/// Taken with [`Hts221::save_config`] and written back with [`Hts221::restore_config`], for
+/// example after the sensor lost power. Convert it to and from bytes to store it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ConfigSnapshot {
+    /// Value of CTRL_REG1.
+    pub ctrl1: u8,
+    /// Value of CTRL_REG2, without the self-clearing boot and one-shot bits. The heater is
+    /// always restored as off, use [`Hts221::heater_pulse`] to run it.
+    pub ctrl2: u8,
+    /// Value of CTRL_REG3.
+    pub ctrl3: u8,
+    /// Value of AV_CONF.
+    pub av_conf: u8,
+}
+
+impl ConfigSnapshot {
+    /// Encode the snapshot as bytes.
+    pub fn to_bytes(&self) -> [u8; 4] {
+        [self.ctrl1, self.ctrl2, self.ctrl3, self.av_conf]
+    }
+
+    /// Decode a snapshot encoded with [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(bytes: [u8; 4]) -> Self {
+        Self {
+            ctrl1: bytes[0],
+            ctrl2: bytes[1],
+            ctrl3: bytes[2],
+            av_conf: bytes[3],
+        }
+    }
+
+    fn config(&self) -> Config {
+        let ctrl1: Ctrl1 = self.ctrl1.into();
+        let ctrl3: Ctrl3 = self.ctrl3.into();
+        Config {
+            output_data_rate: ctrl1.current_output_data_rate(),
+            block_data_update: matches!(self.ctrl1.into(), BlockDataUpdate::MsbLsbReading),
+            averaging: self.av_conf.into(),
+            data_ready: DataReadyConfig {
+                enabled: ctrl3.enable,
+                active_state: ctrl3.active,
+                mode: ctrl3.mode,
+            },
+        }
+    }
+}
+
+impl<I> Hts221<I2cInterface<I>>
+where
+    I: I2c<SevenBitAddress>,
+{
+    /// Create a new instance of the driver using a given I2C peripheral, applying `config`
+    /// when initialized.
+    pub fn with_config(i2c: I, config: Config) -> Self {
+        let mut hts221 = Self::new(i2c);
+        hts221.config = config;
+        hts221
+    }
+}
+
+impl<I> Hts221<I>
+where
+    I: Interface,
+{
+    /// The configuration applied by the driver.
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Switch to a new configuration, writing only the registers which differ from the
+    /// current one. Before initialization, the configuration is only stored.
+    pub async fn reconfigure(&mut self, config: Config) -> Result<(), Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = self.apply_config(config).await;
+        self.track(result)
+    }
+
+    /// Take a snapshot of the device configuration registers.
+    pub async fn save_config(&mut self) -> Result<ConfigSnapshot, Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = self.read_snapshot().await;
+        self.track(result)
+    }
+
+    /// Write back a snapshot taken with [`save_config`](Self::save_config).
+    ///
+    /// The heater is switched off, even if it was on when the snapshot was taken, so a
+    /// restored configuration cannot leave it running.
+    ///
+    /// The calibration data is loaded as well if the driver was not initialized, so sensor
+    /// values can be read right away.
+    pub async fn restore_config(
+        &mut self,
+        snapshot: &ConfigSnapshot,
+    ) -> Result<(), Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = self.write_snapshot(snapshot).await;
+        self.track(result)
+    }
+
+    async fn read_snapshot(&mut self) -> Result<ConfigSnapshot, Hts221Error<I::Error>> {
+        let heater = Ctrl2::read(&mut self.interface).await?.heater_enabled();
+        Ok(ConfigSnapshot {
+            ctrl1: Ctrl1::read(&mut self.interface).await?.into(),
+            ctrl2: heater_only(heater).into(),
+            ctrl3: Ctrl3::read(&mut self.interface).await?.into(),
+            av_conf: AvConf::read(&mut self.interface).await?.into(),
+        })
+    }
+
+    async fn write_snapshot(
+        &mut self,
+        snapshot: &ConfigSnapshot,
+    ) -> Result<(), Hts221Error<I::Error>> {
+        if self.calibration.is_none() {
+            let calibration = Calibration::read(&mut self.interface).await?;
+            if !calibration.is_valid() {
+                return Err(Hts221Error::BadCalibration);
+            }
+            self.calibration.replace(calibration);
+        }
+        self.write_config_register(AV_CONF, snapshot.av_conf, AV_CONF_BITS)
+            .await?;
+        Ctrl2::write(&mut self.interface, heater_only(false)).await?;
+        self.heater_switched(false);
+        self.write_config_register(CTRL_REG3, snapshot.ctrl3, CTRL_REG3_BITS)
+            .await?;
+        // The power mode is in CTRL_REG1, so it is written last
+        self.write_config_register(CTRL_REG1, snapshot.ctrl1, CTRL_REG1_BITS)
+            .await?;
+        self.config = snapshot.config();
+        Ok(())
+    }
+
+    async fn apply_config(&mut self, config: Config) -> Result<(), Hts221Error<I::Error>> {
+        if self.calibration.is_some() {
+            let current = self.config;
+            if config.output_data_rate != current.output_data_rate
+                || config.block_data_update != current.block_data_update
+            {
+                self.write_ctrl1(&config).await?;
+            }
+            if config.data_ready != current.data_ready {
+                self.write_ctrl3(&config).await?;
+            }
+            if config.averaging != current.averaging {
+                self.write_av_conf(&config).await?;
+            }
+        }
+        self.config = config;
+        Ok(())
+    }
+
+    pub(crate) async fn write_ctrl1(
+        &mut self,
+        config: &Config,
+    ) -> Result<(), Hts221Error<I::Error>> {
+        let mut ctrl1 = Ctrl1::read(&mut self.interface).await?;
+        ctrl1
+            .power_active()
+            .output_data_rate(config.output_data_rate)
+            .block_data_update(if config.block_data_update {
+                BlockDataUpdate::MsbLsbReading
+            } else {
+                BlockDataUpdate::Continuous
+            });
+        self.write_config_register(CTRL_REG1, ctrl1.into(), CTRL_REG1_BITS)
+            .await
+    }
+
+    pub(crate) async fn write_ctrl3(
+        &mut self,
+        config: &Config,
+    ) -> Result<(), Hts221Error<I::Error>> {
+        let mut ctrl3 = Ctrl3::read(&mut self.interface).await?;
+        ctrl3
+            .enable(config.data_ready.enabled)
+            .active_state(config.data_ready.active_state)
+            .ready_mode(config.data_ready.mode);
+        self.write_config_register(CTRL_REG3, ctrl3.into(), CTRL_REG3_BITS)
+            .await
+    }
+
+    pub(crate) async fn write_av_conf(
+        &mut self,
+        config: &Config,
+    ) -> Result<(), Hts221Error<I::Error>> {
+        self.write_config_register(AV_CONF, config.averaging.into(), AV_CONF_BITS)
+            .await
+    }
+
+    // Write a configuration register, reading it back when verification is enabled
+    async fn write_config_register(
+        &mut self,
+        register: u8,
+        value: u8,
+        bits: u8,
+    ) -> Result<(), Hts221Error<I::Error>> {
+        self.interface.write_register(register, value).await?;
+        if self.verify_config {
+            self.verify(register, value, bits).await?;
+        }
+        Ok(())
+    }
+
+    // Read back a register and compare it to the written value, ignoring the reserved bits
+    async fn verify(
+        &mut self,
+        register: u8,
+        written: u8,
+        bits: u8,
+    ) -> Result<(), Hts221Error<I::Error>> {
+        let mut buf = [0; 1];
+        self.interface.read_registers(register, &mut buf).await?;
+        let read = buf[0];
+        if written & bits == read & bits {
+            Ok(())
+        } else {
+            Err(Hts221Error::ConfigVerifyFailed {
+                register,
+                written,
+                read,
+            })
+        }
+    }
+}
+
+// CTRL_REG2 with only the heater bit, as the boot and one-shot bits clear themselves
+fn heater_only(heater: bool) -> Ctrl2 {
+    let mut ctrl2: Ctrl2 = 0.into();
+    ctrl2.heater(heater);
+    ctrl2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn reconfigure(stuck_bits: (u8, u8)) -> Result<(), Hts221Error<FakeError>> {
+        let mut hts221 = initialized();
+        hts221.set_verify_config(true);
+        sensor(&mut hts221).stuck_bits = Some(stuck_bits);
+        let mut config = hts221.config();
+        config.output_data_rate = OutputDataRate::Hz7;
+        config.data_ready.enabled = false;
+        config.averaging = 0x12.into();
+        block_on(hts221.reconfigure(config))
+    }
+
+    #[test]
+    fn restore_switches_heater_off() {
+        let mut hts221 = initialized();
+        let snapshot = ConfigSnapshot {
+            ctrl2: heater_only(true).into(),
+            ..block_on(hts221.save_config()).unwrap()
+        };
+        sensor(&mut hts221).regs[CTRL_REG2 as usize] = heater_only(true).into();
+        block_on(hts221.restore_config(&snapshot)).unwrap();
+        assert!(!sensor(&mut hts221).heater_on());
+    }
+
+    #[test]
+    fn restore_is_verified() {
+        let mut hts221 = initialized();
+        let snapshot = block_on(hts221.save_config()).unwrap();
+        hts221.set_verify_config(true);
+        block_on(hts221.restore_config(&snapshot)).unwrap();
+
+        sensor(&mut hts221).stuck_bits = Some((crate::testing::CTRL_REG3, 0b1000_0000));
+        assert!(matches!(
+            block_on(hts221.restore_config(&snapshot)),
+            Err(Hts221Error::ConfigVerifyFailed { register: 0x22, .. })
+        ));
+    }
+
+    #[test]
+    fn restore_ends_cancelled_pulse_first() {
+        let mut hts221 = initialized();
+        let snapshot = block_on(hts221.save_config()).unwrap();
+        let mut stall = Stall;
+        let mut pulse = Box::pin(hts221.heater_pulse(&mut stall, 1000));
+        assert!(poll_once(&mut pulse).is_pending());
+        drop(pulse);
+        sensor(&mut hts221).writes.clear();
+
+        block_on(hts221.restore_config(&snapshot)).unwrap();
+        // The heater is switched off before anything else is written
+        assert_eq!(sensor(&mut hts221).writes[0].0, crate::testing::CTRL_REG2);
+        assert!(!sensor(&mut hts221).heater_on());
+    }
+
+    #[test]
+    fn verify_ignores_reserved_bits() {
+        assert!(reconfigure((crate::testing::CTRL_REG1, 0b0111_1000)).is_ok());
+        assert!(reconfigure((crate::testing::CTRL_REG3, 0b0011_1011)).is_ok());
+        assert!(reconfigure((crate::testing::AV_CONF, 0b1100_0000)).is_ok());
+    }
+
+    #[test]
+    fn verify_detects_mismatch() {
+        assert!(matches!(
+            reconfigure((crate::testing::CTRL_REG3, 0b1000_0000)),
+            Err(Hts221Error::ConfigVerifyFailed { register: 0x22, .. })
+        ));
+        assert!(matches!(
+            reconfigure((crate::testing::AV_CONF, 0b0010_0000)),
+            Err(Hts221Error::ConfigVerifyFailed { register: 0x10, .. })
+        ));
+    }
+
+    #[test]
+    fn writes_only_changed_registers() {
+        let mut hts221 = initialized();
+        sensor(&mut hts221).writes.clear();
+        let mut config = hts221.config();
+        block_on(hts221.reconfigure(config)).unwrap();
+        assert!(sensor(&mut hts221).writes.is_empty());
+
+        config.data_ready.enabled = !config.data_ready.enabled;
+        block_on(hts221.reconfigure(config)).unwrap();
+        let writes = &sensor(&mut hts221).writes;
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0].0, crate::testing::CTRL_REG3);
+    }
+
+    #[test]
+    fn applies_config_on_initialize() {
+        let config = Config {
+            output_data_rate: OutputDataRate::Hz12p5,
+            ..Config::default()
+        };
+        let mut hts221 = Hts221::with_config(FakeSensor::new(), config);
+        block_on(hts221.initialize()).unwrap();
+        assert_eq!(
+            block_on(hts221.current_odr()).unwrap(),
+            OutputDataRate::Hz12p5
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use super::{Celsius, SensorAcquisition};
+
+/// Direction in which the controlled device moves the measured value.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ControlAction {
+    /// The device raises the value when on, such as a heater or humidifier.
+    Raise,
+    /// The device lowers the value when on, such as a fan, cooler or dehumidifier.
+    Lower,
+}
+
+/// On/off controller with hysteresis, for thermostats and humidistats.
+///
+/// The output switches on once the value is more than half the hysteresis away from the
+/// setpoint in the direction the device corrects, and switches off once it has moved half
+/// the hysteresis past the setpoint.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HysteresisController {
+    setpoint: f32,
+    hysteresis: f32,
+    action: ControlAction,
+    on: bool,
+}
+
+impl HysteresisController {
+    /// Create a controller, initially off.
+    pub fn new(setpoint: f32, hysteresis: f32, action: ControlAction) -> Self {
+        Self {
+            setpoint,
+            hysteresis,
+            action,
+            on: false,
+        }
+    }
+
+    /// Change the setpoint.
+    pub fn set_setpoint(&mut self, setpoint: f32) {
+        self.setpoint = setpoint;
+    }
+
+    /// Change the hysteresis.
+    pub fn set_hysteresis(&mut self, hysteresis: f32) {
+        self.hysteresis = hysteresis;
+    }
+
+    /// Evaluate a value, returning whether the output is on.
+    pub fn update(&mut self, value: f32) -> bool {
+        let low = self.setpoint - self.hysteresis / 2.0;
+        let high = self.setpoint + self.hysteresis / 2.0;
+        let (switch_on, switch_off) = match self.action {
+            ControlAction::Raise => (value < low, value > high),
+            ControlAction::Lower => (value > high, value < low),
+        };
+        if switch_on {
+            self.on = true;
+        } else if switch_off {
+            self.on = false;
+        }
+        self.on
+    }
+
+    /// Evaluate the temperature of an acquisition, returning whether the output is on.
+    pub fn update_temperature(&mut self, acquisition: &SensorAcquisition<Celsius>) -> bool {
+        self.update(acquisition.temperature.value)
+    }
+
+    /// Evaluate the relative humidity of an acquisition, returning whether the output is on.
+    pub fn update_humidity(&mut self, acquisition: &SensorAcquisition<Celsius>) -> bool {
+        self.update(acquisition.relative_humidity)
+    }
+
+    /// Whether the output is currently on.
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switches_heater_with_hysteresis() {
+        let mut controller = HysteresisController::new(20.0, 2.0, ControlAction::Raise);
+        assert!(!controller.update(19.5));
+        assert!(controller.update(18.9));
+        assert!(controller.update(20.9));
+        assert!(!controller.update(21.1));
+        assert!(!controller.update(19.5));
+    }
+
+    #[test]
+    fn switches_dehumidifier_with_hysteresis() {
+        let mut controller = HysteresisController::new(50.0, 4.0, ControlAction::Lower);
+        assert!(controller.update(52.1));
+        assert!(controller.update(49.0));
+        assert!(!controller.update(47.9));
+        assert!(!controller.is_on());
+
+        controller.set_setpoint(40.0);
+        assert!(controller.update_humidity(&SensorAcquisition {
+            temperature: 20.0.into(),
+            relative_humidity: 47.9,
+        }));
+    }
+}
@@ -0,0 +1,94 @@
+use super::{write_fixed, Celsius, SensorAcquisition, HAS_HUMIDITY, HAS_TEMPERATURE};
+use core::fmt::{Result, Write};
+
+/// Encoder writing acquisitions as CSV lines, for data loggers.
+///
+/// Values are written with a fixed number of decimals and without allocation, to any
+/// [`Write`] implementation such as a `heapless::String` or a UART adapter.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CsvEncoder {
+    precision: u8,
+    timestamp: bool,
+}
+
+impl CsvEncoder {
+    /// Create an encoder writing values with `precision` decimals (at most 6), and with a
+    /// leading timestamp column if `timestamp` is set.
+    pub fn new(precision: u8, timestamp: bool) -> Self {
+        Self {
+            precision,
+            timestamp,
+        }
+    }
+
+    /// Write the header line naming the columns.
+    pub fn write_header<W: Write>(&self, out: &mut W) -> Result {
+        if self.timestamp {
+            out.write_str("timestamp_ms,")?;
+        }
+        out.write_str("temperature_c,relative_humidity\n")
+    }
+
+    /// Write a line for an acquisition. The timestamp column is left empty when no
+    /// timestamp is available, and omitted when the encoder has no timestamp column. The
+    /// column of a channel compiled out with the `temperature-only` or `humidity-only`
+    /// feature is left empty.
+    pub fn write_row<W: Write>(
+        &self,
+        out: &mut W,
+        acquisition: &SensorAcquisition<Celsius>,
+        timestamp_ms: Option<u64>,
+    ) -> Result {
+        if self.timestamp {
+            if let Some(timestamp_ms) = timestamp_ms {
+                write!(out, "{}", timestamp_ms)?;
+            }
+            out.write_char(',')?;
+        }
+        if HAS_TEMPERATURE {
+            write_fixed(out, acquisition.temperature.value, self.precision)?;
+        }
+        out.write_char(',')?;
+        if HAS_HUMIDITY {
+            write_fixed(out, acquisition.relative_humidity, self.precision)?;
+        }
+        out.write_char('\n')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(temperature: f32, relative_humidity: f32, timestamp_ms: Option<u64>) -> String {
+        let acquisition = SensorAcquisition {
+            temperature: temperature.into(),
+            relative_humidity,
+        };
+        let mut out = String::new();
+        CsvEncoder::new(2, true)
+            .write_row(&mut out, &acquisition, timestamp_ms)
+            .unwrap();
+        out
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+    fn writes_rows() {
+        assert_eq!(row(23.456, 45.0, Some(1000)), "1000,23.46,45.00\n");
+        assert_eq!(row(-0.5, 100.0, None), ",-0.50,100.00\n");
+    }
+
+    #[test]
+    #[cfg(feature = "temperature-only")]
+    fn leaves_humidity_empty() {
+        assert_eq!(row(23.456, f32::NAN, Some(1000)), "1000,23.46,\n");
+    }
+
+    #[test]
+    #[cfg(feature = "humidity-only")]
+    fn leaves_temperature_empty() {
+        assert_eq!(row(f32::NAN, 45.0, Some(1000)), "1000,,45.00\n");
+    }
+}
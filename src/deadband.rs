@@ -0,0 +1,103 @@
+use super::{Celsius, SensorAcquisition};
+
+/// Dead-band stage for relative humidity, for humidistat control loops.
+///
+/// The humidity is only propagated when it moves further than the band from the last
+/// propagated value, so noise around a setpoint does not make a relay chatter. The output
+/// is a regular acquisition, so it can be passed on to monitors such as
+/// `CondensationMonitor`.
+pub struct HumidityDeadBand {
+    band: f32,
+    held: Option<f32>,
+    changed: bool,
+}
+
+impl HumidityDeadBand {
+    /// Create a filter propagating changes larger than `band` percentage points.
+    pub fn new(band: f32) -> Self {
+        Self {
+            band,
+            held: None,
+            changed: false,
+        }
+    }
+
+    /// Filter an acquisition, replacing its humidity with the held value unless it moved
+    /// further than the band. The temperature is passed through.
+    pub fn update(
+        &mut self,
+        acquisition: &SensorAcquisition<Celsius>,
+    ) -> SensorAcquisition<Celsius> {
+        let humidity = acquisition.relative_humidity;
+        self.changed = match self.held {
+            Some(held) => humidity - held > self.band || held - humidity > self.band,
+            None => true,
+        };
+        let held = if self.changed {
+            *self.held.insert(humidity)
+        } else {
+            self.held.unwrap_or(humidity)
+        };
+        SensorAcquisition {
+            temperature: acquisition.temperature,
+            relative_humidity: held,
+        }
+    }
+
+    /// Whether the last update propagated a new humidity value.
+    pub fn changed(&self) -> bool {
+        self.changed
+    }
+
+    /// The humidity value currently held, if any acquisition was filtered yet.
+    pub fn held(&self) -> Option<f32> {
+        self.held
+    }
+
+    /// Forget the held value, so the next acquisition is propagated unchanged.
+    pub fn reset(&mut self) {
+        self.held = None;
+        self.changed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acquisition(temperature: f32, relative_humidity: f32) -> SensorAcquisition<Celsius> {
+        SensorAcquisition {
+            temperature: temperature.into(),
+            relative_humidity,
+        }
+    }
+
+    #[test]
+    fn holds_humidity_within_band() {
+        let mut deadband = HumidityDeadBand::new(2.0);
+        assert_eq!(
+            deadband.update(&acquisition(20.0, 50.0)).relative_humidity,
+            50.0
+        );
+        assert!(deadband.changed());
+
+        let filtered = deadband.update(&acquisition(21.0, 51.5));
+        assert_eq!(filtered.relative_humidity, 50.0);
+        assert_eq!(filtered.temperature.value, 21.0);
+        assert!(!deadband.changed());
+
+        assert_eq!(
+            deadband.update(&acquisition(21.0, 47.5)).relative_humidity,
+            47.5
+        );
+        assert!(deadband.changed());
+        assert_eq!(deadband.held(), Some(47.5));
+
+        deadband.reset();
+        assert_eq!(deadband.held(), None);
+        assert_eq!(
+            deadband.update(&acquisition(21.0, 48.0)).relative_humidity,
+            48.0
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use super::{math, Celsius, SensorAcquisition, Temperature};
+
+// Magnus formula coefficients over water (Sonntag 1990)
+const MAGNUS_A: f32 = 6.112;
+const MAGNUS_B: f32 = 17.62;
+const MAGNUS_C: f32 = 243.12;
+
+// Arden Buck equation coefficients over water (Buck 1996)
+const BUCK_A: f32 = 6.1121;
+const BUCK_B: f32 = 18.678;
+const BUCK_C: f32 = 257.14;
+const BUCK_D: f32 = 234.5;
+
+/// Standard sea-level pressure in hPa, assumed when no pressure is supplied.
+pub const STANDARD_PRESSURE: f32 = 1013.25;
+
+// Ratio of the molar masses of water and dry air
+const EPSILON: f32 = 0.622;
+
+// Specific gas constant for water vapor, in J/(kg*K)
+const R_WATER_VAPOR: f32 = 461.5;
+
+/// Formula used to compute the saturation vapor pressure over water.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum VaporPressureFormula {
+    /// Magnus formula, with Sonntag coefficients.
+    Magnus,
+    /// Arden Buck equation, more accurate at the ends of the range.
+    ArdenBuck,
+}
+
+/// Saturation vapor pressure over water in hPa at the given temperature.
+pub fn saturation_vapor_pressure(
+    temperature: Temperature<Celsius>,
+    formula: VaporPressureFormula,
+) -> f32 {
+    let t = temperature.raw_value();
+    match formula {
+        VaporPressureFormula::Magnus => MAGNUS_A * math::exp((MAGNUS_B * t) / (MAGNUS_C + t)),
+        VaporPressureFormula::ArdenBuck => {
+            BUCK_A * math::exp((BUCK_B - t / BUCK_D) * (t / (BUCK_C + t)))
+        }
+    }
+}
+
+impl SensorAcquisition<Celsius> {
+    /// Dew point temperature, using the Magnus formula.
+    ///
+    /// In dry air the dew point reaches the lower limit of the formula, -243.12 °C.
+    pub fn dew_point(&self) -> Temperature<Celsius> {
+        let t = self.temperature.raw_value();
+        let gamma = math::ln(self.relative_humidity / 100.0) + (MAGNUS_B * t) / (MAGNUS_C + t);
+        // C * gamma / (B - gamma), arranged to stay finite as gamma tends to minus infinity
+        Temperature::new(MAGNUS_C * MAGNUS_B / (MAGNUS_B - gamma) - MAGNUS_C)
+    }
+
+    /// Partial pressure of water vapor in hPa, using the given formula.
+    pub fn vapor_pressure(&self, formula: VaporPressureFormula) -> f32 {
+        saturation_vapor_pressure(self.temperature, formula) * self.relative_humidity / 100.0
+    }
+
+    /// Difference between the temperature and the dew point, in degrees celsius.
+    ///
+    /// Condensation forms on surfaces at the measured temperature as the margin approaches zero.
+    pub fn condensation_margin(&self) -> f32 {
+        self.temperature.raw_value() - self.dew_point().raw_value()
+    }
+
+    /// Approximate indoor Wet Bulb Globe Temperature, for heat-stress monitoring.
+    ///
+    /// Uses the simplified formula of the Australian Bureau of Meteorology, which assumes
+    /// shade and light wind; it is not a substitute for a globe thermometer.
+    pub fn wbgt_indoor(&self) -> Temperature<Celsius> {
+        let e = self.vapor_pressure(VaporPressureFormula::Magnus);
+        Temperature::new(0.567 * self.temperature.raw_value() + 0.393 * e + 3.94)
+    }
+
+    /// Absolute humidity in g/m³, assuming standard sea-level pressure.
+    ///
+    /// See [`absolute_humidity_at`](Self::absolute_humidity_at) to supply a measured pressure.
+    pub fn absolute_humidity(&self) -> f32 {
+        self.absolute_humidity_at(STANDARD_PRESSURE)
+    }
+
+    /// Absolute humidity in g/m³, at the given barometric pressure in hPa.
+    pub fn absolute_humidity_at(&self, pressure: f32) -> f32 {
+        let e = self.moist_vapor_pressure(pressure);
+        let t = self.temperature.raw_value() + 273.15;
+        // e in hPa to Pa, kg to g
+        e * 100.0 / (R_WATER_VAPOR * t) * 1000.0
+    }
+
+    /// Mixing ratio in g/kg of dry air, assuming standard sea-level pressure.
+    ///
+    /// See [`mixing_ratio_at`](Self::mixing_ratio_at) to supply a measured pressure.
+    pub fn mixing_ratio(&self) -> f32 {
+        self.mixing_ratio_at(STANDARD_PRESSURE)
+    }
+
+    /// Mixing ratio in g/kg of dry air, at the given barometric pressure in hPa.
+    pub fn mixing_ratio_at(&self, pressure: f32) -> f32 {
+        let e = self.moist_vapor_pressure(pressure);
+        EPSILON * e / (pressure - e) * 1000.0
+    }
+
+    // Vapor pressure in moist air, corrected with the enhancement factor (Buck 1981).
+    fn moist_vapor_pressure(&self, pressure: f32) -> f32 {
+        let enhancement = 1.0007 + 3.46e-6 * pressure;
+        self.vapor_pressure(VaporPressureFormula::ArdenBuck) * enhancement
+    }
+}
+
+/// Monitor raising an alert when the condensation margin falls below a threshold.
+///
+/// The alert is invoked once when the margin drops below the threshold, and is re-armed when
+/// the margin recovers above it.
+pub struct CondensationMonitor {
+    threshold: f32,
+    alert: fn(f32),
+    alerted: bool,
+}
+
+impl CondensationMonitor {
+    /// Create a monitor with a threshold in degrees celsius, calling `alert` with the margin.
+    pub fn new(threshold: f32, alert: fn(f32)) -> Self {
+        Self {
+            threshold,
+            alert,
+            alerted: false,
+        }
+    }
+
+    /// Change the alert threshold.
+    pub fn set_threshold(&mut self, threshold: f32) {
+        self.threshold = threshold;
+    }
+
+    /// Evaluate an acquisition, returning its condensation margin.
+    pub fn update(&mut self, acquisition: &SensorAcquisition<Celsius>) -> f32 {
+        let margin = acquisition.condensation_margin();
+        if margin < self.threshold {
+            if !self.alerted {
+                self.alerted = true;
+                (self.alert)(margin);
+            }
+        } else {
+            self.alerted = false;
+        }
+        margin
+    }
+
+    /// Whether the margin is currently below the threshold.
+    pub fn alerted(&self) -> bool {
+        self.alerted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(celsius: f32, relative_humidity: f32) -> SensorAcquisition<Celsius> {
+        SensorAcquisition {
+            temperature: celsius.into(),
+            relative_humidity,
+        }
+    }
+
+    fn assert_near(value: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (value - expected).abs() <= tolerance,
+            "{} is not within {} of {}",
+            value,
+            tolerance,
+            expected
+        );
+    }
+
+    // The micromath exponential is within 0.25 %
+    const EXP_TOLERANCE: f32 = if cfg!(feature = "micromath") {
+        0.003
+    } else {
+        0.0005
+    };
+
+    #[test]
+    fn saturation_vapor_pressure_reference() {
+        let cases = [
+            (0.0, VaporPressureFormula::Magnus, 6.112),
+            (20.0, VaporPressureFormula::Magnus, 23.33),
+            (20.0, VaporPressureFormula::ArdenBuck, 23.39),
+            (40.0, VaporPressureFormula::ArdenBuck, 73.8),
+            (-10.0, VaporPressureFormula::ArdenBuck, 2.865),
+        ];
+        for (celsius, formula, expected) in cases {
+            let pressure = saturation_vapor_pressure(Temperature::new(celsius), formula);
+            assert_near(pressure, expected, expected * EXP_TOLERANCE);
+        }
+    }
+
+    #[test]
+    fn saturation_vapor_pressure_of_nan() {
+        for formula in [
+            VaporPressureFormula::Magnus,
+            VaporPressureFormula::ArdenBuck,
+        ] {
+            assert!(saturation_vapor_pressure(Temperature::new(f32::NAN), formula).is_nan());
+        }
+    }
+
+    #[test]
+    fn moisture_reference() {
+        let cases = [
+            (25.0, 60.0, STANDARD_PRESSURE, 13.87, 11.94),
+            (25.0, 60.0, 850.0, 13.86, 14.28),
+            (0.0, 100.0, STANDARD_PRESSURE, 4.87, 3.79),
+        ];
+        for (celsius, relative_humidity, pressure, absolute, mixing) in cases {
+            let acquisition = at(celsius, relative_humidity);
+            let tolerance = |expected: f32| expected * EXP_TOLERANCE + 0.01;
+            assert_near(
+                acquisition.absolute_humidity_at(pressure),
+                absolute,
+                tolerance(absolute),
+            );
+            assert_near(
+                acquisition.mixing_ratio_at(pressure),
+                mixing,
+                tolerance(mixing),
+            );
+        }
+        let acquisition = at(25.0, 60.0);
+        assert_eq!(
+            acquisition.absolute_humidity(),
+            acquisition.absolute_humidity_at(STANDARD_PRESSURE)
+        );
+        assert_eq!(
+            acquisition.mixing_ratio(),
+            acquisition.mixing_ratio_at(STANDARD_PRESSURE)
+        );
+    }
+
+    #[test]
+    fn enhancement_factor() {
+        let acquisition = at(25.0, 60.0);
+        let dry = acquisition.vapor_pressure(VaporPressureFormula::ArdenBuck);
+        let factor = |pressure| acquisition.moist_vapor_pressure(pressure) / dry;
+        assert_near(factor(STANDARD_PRESSURE), 1.00421, 0.00001);
+        assert_near(factor(500.0), 1.00243, 0.00001);
+    }
+
+    #[test]
+    fn moisture_in_dry_air() {
+        let dry = at(25.0, 0.0);
+        assert_eq!(dry.vapor_pressure(VaporPressureFormula::Magnus), 0.0);
+        assert_eq!(dry.absolute_humidity(), 0.0);
+        assert_eq!(dry.mixing_ratio(), 0.0);
+    }
+
+    #[test]
+    fn moisture_of_nan() {
+        for acquisition in [at(f32::NAN, 50.0), at(25.0, f32::NAN)] {
+            assert!(acquisition.absolute_humidity().is_nan());
+            assert!(acquisition.mixing_ratio_at(850.0).is_nan());
+        }
+    }
+
+    #[test]
+    fn wbgt_reference() {
+        assert_near(at(30.0, 50.0).wbgt_indoor().raw_value(), 29.27, 0.02);
+        assert_near(at(20.0, 40.0).wbgt_indoor().raw_value(), 18.95, 0.02);
+        // Dry air leaves the temperature term alone
+        assert_near(at(25.0, 0.0).wbgt_indoor().raw_value(), 18.115, 0.001);
+    }
+
+    #[test]
+    fn wbgt_of_nan() {
+        assert!(at(f32::NAN, 50.0).wbgt_indoor().raw_value().is_nan());
+        assert!(at(25.0, f32::NAN).wbgt_indoor().raw_value().is_nan());
+    }
+
+    #[test]
+    fn dew_point_reference() {
+        assert_near(at(25.0, 60.0).dew_point().raw_value(), 16.7, 0.1);
+        assert_near(at(10.0, 90.0).dew_point().raw_value(), 8.4, 0.1);
+        assert_near(at(-5.0, 80.0).dew_point().raw_value(), -7.9, 0.1);
+        assert_near(at(30.0, 100.0).dew_point().raw_value(), 30.0, 0.05);
+    }
+
+    #[test]
+    fn dew_point_in_dry_air() {
+        assert_near(at(25.0, 0.0).dew_point().raw_value(), -MAGNUS_C, 0.001);
+    }
+
+    #[test]
+    fn dew_point_of_nan() {
+        assert!(at(f32::NAN, 50.0).dew_point().raw_value().is_nan());
+        assert!(at(25.0, f32::NAN).dew_point().raw_value().is_nan());
+    }
+
+    #[test]
+    fn condensation_margin_reference() {
+        assert_near(at(25.0, 60.0).condensation_margin(), 8.3, 0.1);
+        assert_near(at(30.0, 100.0).condensation_margin(), 0.0, 0.05);
+        assert_near(at(25.0, 0.0).condensation_margin(), 25.0 + MAGNUS_C, 0.001);
+        assert!(at(25.0, f32::NAN).condensation_margin().is_nan());
+    }
+
+    #[test]
+    fn alerts_once_until_the_margin_recovers() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        static ALERTS: AtomicUsize = AtomicUsize::new(0);
+
+        let mut monitor = CondensationMonitor::new(2.0, |_| {
+            ALERTS.fetch_add(1, Ordering::Relaxed);
+        });
+        monitor.update(&at(25.0, 60.0));
+        assert!(!monitor.alerted());
+        monitor.update(&at(25.0, 95.0));
+        monitor.update(&at(25.0, 97.0));
+        assert!(monitor.alerted());
+        assert_eq!(ALERTS.load(Ordering::Relaxed), 1);
+        monitor.update(&at(25.0, 60.0));
+        assert!(!monitor.alerted());
+        monitor.update(&at(25.0, 95.0));
+        assert_eq!(ALERTS.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,150 @@
+use super::{within, Celsius, SensorAcquisition, HAS_HUMIDITY, HAS_TEMPERATURE};
+
+/// Offsets to add to readings to match a reference instrument.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DriftCorrection {
+    /// Temperature offset in degrees celsius.
+    pub temperature: f32,
+    /// Relative humidity offset in percentage points.
+    pub relative_humidity: f32,
+}
+
+/// Tracks drift of the sensor against occasional reference measurements.
+///
+/// Each reference measurement, taken with a calibrated instrument next to the sensor,
+/// updates a smoothed estimate of the offset between the two. The estimate can be applied
+/// to readings as a correction, and a warning is raised once it exceeds the configured limits.
+pub struct DriftMonitor {
+    temperature_limit: f32,
+    humidity_limit: f32,
+    smoothing: f32,
+    correction: DriftCorrection,
+    references: u32,
+}
+
+impl DriftMonitor {
+    /// Create a monitor warning when the estimated drift exceeds `temperature_limit` degrees
+    /// or `humidity_limit` percentage points.
+    ///
+    /// `smoothing` is the weight of a new reference measurement in the estimate, between 0 and 1.
+    pub fn new(temperature_limit: f32, humidity_limit: f32, smoothing: f32) -> Self {
+        Self {
+            temperature_limit,
+            humidity_limit,
+            smoothing,
+            correction: DriftCorrection::default(),
+            references: 0,
+        }
+    }
+
+    /// Add a reference measurement taken at the same time as `reading`.
+    ///
+    /// A channel compiled out with the `temperature-only` or `humidity-only` feature keeps
+    /// a zero offset.
+    pub fn add_reference(
+        &mut self,
+        reading: &SensorAcquisition<Celsius>,
+        reference: &SensorAcquisition<Celsius>,
+    ) {
+        let temperature = reference.temperature.value - reading.temperature.value;
+        let relative_humidity = reference.relative_humidity - reading.relative_humidity;
+        // The first reference is taken as is, later ones are smoothed into the estimate
+        let weight = if self.references == 0 {
+            1.0
+        } else {
+            self.smoothing
+        };
+        if HAS_TEMPERATURE {
+            self.correction.temperature += (temperature - self.correction.temperature) * weight;
+        }
+        if HAS_HUMIDITY {
+            self.correction.relative_humidity +=
+                (relative_humidity - self.correction.relative_humidity) * weight;
+        }
+        self.references = self.references.saturating_add(1);
+    }
+
+    /// Current correction estimate.
+    pub fn correction(&self) -> DriftCorrection {
+        self.correction
+    }
+
+    /// Number of reference measurements added.
+    pub fn references(&self) -> u32 {
+        self.references
+    }
+
+    /// Apply the current correction to a reading.
+    pub fn apply(&self, reading: &SensorAcquisition<Celsius>) -> SensorAcquisition<Celsius> {
+        SensorAcquisition {
+            temperature: reading.temperature + self.correction.temperature,
+            relative_humidity: reading.relative_humidity + self.correction.relative_humidity,
+        }
+    }
+
+    /// Whether the estimated drift exceeds the limits, so the sensor should be serviced.
+    pub fn drift_exceeded(&self) -> bool {
+        !within(self.correction.temperature, 0.0, self.temperature_limit)
+            || !within(self.correction.relative_humidity, 0.0, self.humidity_limit)
+    }
+
+    /// Forget all reference measurements.
+    pub fn reset(&mut self) {
+        self.correction = DriftCorrection::default();
+        self.references = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acquisition(temperature: f32, relative_humidity: f32) -> SensorAcquisition<Celsius> {
+        SensorAcquisition {
+            temperature: temperature.into(),
+            relative_humidity,
+        }
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+    fn smooths_reference_offsets() {
+        let mut monitor = DriftMonitor::new(1.0, 3.0, 0.5);
+        monitor.add_reference(&acquisition(20.0, 50.0), &acquisition(20.5, 52.0));
+        assert_eq!(
+            monitor.correction(),
+            DriftCorrection {
+                temperature: 0.5,
+                relative_humidity: 2.0,
+            }
+        );
+        assert!(!monitor.drift_exceeded());
+
+        monitor.add_reference(&acquisition(20.0, 50.0), &acquisition(21.5, 56.0));
+        assert_eq!(monitor.correction().temperature, 1.0);
+        assert_eq!(monitor.correction().relative_humidity, 4.0);
+        assert!(monitor.drift_exceeded());
+        assert_eq!(monitor.references(), 2);
+
+        let corrected = monitor.apply(&acquisition(10.0, 30.0));
+        assert_eq!(corrected.temperature.value, 11.0);
+        assert_eq!(corrected.relative_humidity, 34.0);
+
+        monitor.reset();
+        assert_eq!(monitor.correction(), DriftCorrection::default());
+    }
+
+    #[test]
+    #[cfg(any(feature = "temperature-only", feature = "humidity-only"))]
+    fn ignores_missing_channel() {
+        let (reading, reference) = if HAS_HUMIDITY {
+            (acquisition(f32::NAN, 50.0), acquisition(f32::NAN, 50.5))
+        } else {
+            (acquisition(20.0, f32::NAN), acquisition(20.5, f32::NAN))
+        };
+        let mut monitor = DriftMonitor::new(1.0, 1.0, 0.5);
+        monitor.add_reference(&reading, &reference);
+        assert!(!monitor.drift_exceeded());
+    }
+}
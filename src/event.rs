@@ -0,0 +1,185 @@
+use super::{
+    sample_available, Celsius, Hts221, Hts221Error, Interface, RecoveryAcquisition,
+    SensorAcquisition, Temperature,
+};
+
+/// A single measured or derived quantity.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Measurement {
+    /// Temperature.
+    Temperature(Temperature<Celsius>),
+    /// Relative humidity in percent.
+    RelativeHumidity(f32),
+    /// Dew point temperature.
+    #[cfg(any(feature = "libm", feature = "micromath"))]
+    DewPoint(Temperature<Celsius>),
+    /// Absolute humidity in g/m³.
+    #[cfg(any(feature = "libm", feature = "micromath"))]
+    AbsoluteHumidity(f32),
+}
+
+/// Flags qualifying how far the values of an acquisition can be trusted.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct QualityFlags {
+    /// The heater ran recently, so values may not have stabilized.
+    pub heater_recently_on: bool,
+    /// No new sample was available, so the values were read before.
+    pub stale: bool,
+    /// The relative humidity was outside of 0 to 100 % and has been clamped.
+    pub clamped: bool,
+    /// The values changed more than allowed since the previous acquisition, so they were
+    /// rejected and the previous values are reported instead. The values are accepted if the
+    /// next acquisition confirms them.
+    pub outlier_rejected: bool,
+    /// The values are outside of the calibration points, so they were extrapolated.
+    pub extrapolated: bool,
+}
+
+impl QualityFlags {
+    /// Whether no flag is raised.
+    pub fn is_good(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Measurement event, the integration point for sensor frameworks and event buses.
+#[derive(Clone, Debug)]
+pub struct MeasurementEvent {
+    /// Values read from the sensor.
+    pub acquisition: SensorAcquisition<Celsius>,
+    /// Quality of the values.
+    pub quality: QualityFlags,
+}
+
+impl MeasurementEvent {
+    /// All measured and derived quantities of the event, without those depending on a channel
+    /// compiled out with the `temperature-only` or `humidity-only` feature.
+    pub fn measurements(&self) -> impl Iterator<Item = Measurement> {
+        [
+            #[cfg(not(feature = "humidity-only"))]
+            Measurement::Temperature(self.acquisition.temperature),
+            #[cfg(not(feature = "temperature-only"))]
+            Measurement::RelativeHumidity(self.acquisition.relative_humidity),
+            #[cfg(all(
+                any(feature = "libm", feature = "micromath"),
+                not(any(feature = "temperature-only", feature = "humidity-only"))
+            ))]
+            Measurement::DewPoint(self.acquisition.dew_point()),
+            #[cfg(all(
+                any(feature = "libm", feature = "micromath"),
+                not(any(feature = "temperature-only", feature = "humidity-only"))
+            ))]
+            Measurement::AbsoluteHumidity(self.acquisition.absolute_humidity()),
+        ]
+        .into_iter()
+    }
+}
+
+impl From<SensorAcquisition<Celsius>> for MeasurementEvent {
+    fn from(acquisition: SensorAcquisition<Celsius>) -> Self {
+        Self {
+            acquisition,
+            quality: QualityFlags::default(),
+        }
+    }
+}
+
+impl From<RecoveryAcquisition> for MeasurementEvent {
+    fn from(recovery: RecoveryAcquisition) -> Self {
+        Self {
+            acquisition: recovery.acquisition,
+            quality: QualityFlags {
+                heater_recently_on: recovery.recovering,
+                ..QualityFlags::default()
+            },
+        }
+    }
+}
+
+impl<I> Hts221<I>
+where
+    I: Interface,
+{
+    /// Reject acquisitions read with [`read_qualified`](Self::read_qualified) whose
+    /// temperature or relative humidity changed by more than the given steps since the
+    /// previous one, or `None` to accept any change.
+    ///
+    /// A single spike is rejected, while a lasting step change is accepted from the second
+    /// acquisition at the new level on.
+    pub fn set_outlier_steps(&mut self, steps: Option<(f32, f32)>) {
+        self.outlier_steps = steps;
+    }
+
+    /// Read sensor values along with flags telling how far they can be trusted.
+    ///
+    /// The heater flag is raised if the heater was switched on since the previous qualified
+    /// read, or the values are affected by it, see [`HeaterPolicy`](super::HeaterPolicy).
+    pub async fn read_qualified(&mut self) -> Result<MeasurementEvent, Hts221Error<I::Error>> {
+        let status = self.status().await?;
+        let mut acquisition = self.read().await?;
+        let heater_recently_on = core::mem::take(&mut self.heater_used);
+        let range = self.range_flags(&acquisition);
+        let mut quality = QualityFlags {
+            heater_recently_on,
+            stale: !sample_available(&status),
+            extrapolated: range.temperature_extrapolated || range.humidity_extrapolated,
+            ..QualityFlags::default()
+        };
+
+        if acquisition.relative_humidity < 0.0 || acquisition.relative_humidity > 100.0 {
+            acquisition.relative_humidity = acquisition.relative_humidity.clamp(0.0, 100.0);
+            quality.clamped = true;
+        }
+
+        if let (Some((t_step, rh_step)), Some(previous)) = (self.outlier_steps, self.previous) {
+            let rejected = self.rejected.take();
+            let confirmed = matches!(rejected, Some(rejected) if acquisition.approx_eq(&rejected, t_step, rh_step));
+            if !confirmed && !acquisition.approx_eq(&previous, t_step, rh_step) {
+                self.rejected.replace(acquisition);
+                acquisition = previous;
+                quality.outlier_rejected = true;
+            }
+        }
+        self.previous.replace(acquisition);
+
+        Ok(MeasurementEvent {
+            acquisition,
+            quality,
+        })
+    }
+}
+
+#[cfg(test)]
+#[cfg(not(feature = "humidity-only"))]
+mod tests {
+    use crate::testing::*;
+
+    fn read(hts221: &mut crate::Hts221<FakeSensor>, celsius: f32) -> (f32, bool) {
+        sensor(hts221).sample_every = None;
+        sensor(hts221).sample(celsius, 50.0);
+        let event = block_on(hts221.read_qualified()).unwrap();
+        (
+            event.acquisition.temperature.raw_value(),
+            event.quality.outlier_rejected,
+        )
+    }
+
+    #[test]
+    fn rejects_spike() {
+        let mut hts221 = initialized();
+        hts221.set_outlier_steps(Some((1.0, 5.0)));
+        assert_eq!(read(&mut hts221, 22.0), (22.0, false));
+        assert_eq!(read(&mut hts221, 28.0), (22.0, true));
+        assert_eq!(read(&mut hts221, 22.5), (22.5, false));
+    }
+
+    #[test]
+    fn accepts_step_change() {
+        let mut hts221 = initialized();
+        hts221.set_outlier_steps(Some((1.0, 5.0)));
+        assert_eq!(read(&mut hts221, 22.0), (22.0, false));
+        assert_eq!(read(&mut hts221, 28.0), (22.0, true));
+        assert_eq!(read(&mut hts221, 28.0), (28.0, false));
+        assert_eq!(read(&mut hts221, 28.5), (28.5, false));
+    }
+}
@@ -0,0 +1,35 @@
+//! Minimal executor polling a future to completion in place, shared by the blocking entry
+//! points and the unit tests.
+
+use core::{
+    future::Future,
+    pin::Pin,
+    ptr,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+/// Run a future to completion, polling it in a loop.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = future;
+    // SAFETY: the future is shadowed, so it is not moved again
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// A waker which does nothing when woken.
+pub(crate) fn noop_waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        |_| RawWaker::new(ptr::null(), &VTABLE),
+        |_| {},
+        |_| {},
+        |_| {},
+    );
+    // SAFETY: the vtable functions do nothing, so any data pointer is fine
+    unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) }
+}
@@ -0,0 +1,111 @@
+use super::{Hts221, Hts221Error, Interface};
+
+/// How sensor reads handle values acquired while the heater is on, or shortly after.
+///
+/// Such values reflect the heater rather than the environment.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum HeaterPolicy {
+    /// Return the values, flagged as
+    /// [`heater_recently_on`](super::QualityFlags::heater_recently_on) by
+    /// [`read_qualified`](Hts221::read_qualified).
+    #[default]
+    Flag,
+    /// Fail reads with [`Hts221Error::HeaterActive`].
+    Refuse,
+    /// Skip the samples while waiting for new ones, as in [`read_into`](Hts221::read_into)
+    /// and [`samples`](Hts221::samples). Other reads fail with [`Hts221Error::HeaterActive`].
+    Discard,
+}
+
+impl<I> Hts221<I>
+where
+    I: Interface,
+{
+    /// Set how reads handle values affected by the heater: while it is on and for the
+    /// following `settle_reads` reads after it is switched off.
+    pub fn set_heater_policy(&mut self, policy: HeaterPolicy, settle_reads: u32) {
+        self.heater_policy = policy;
+        self.heater_settle_reads = settle_reads;
+    }
+
+    /// Whether the next read is affected by the heater, either on or still settling.
+    pub fn heater_active(&self) -> bool {
+        self.heater_on || self.heater_settling > 0
+    }
+
+    pub(crate) fn heater_switched(&mut self, on: bool) {
+        if self.heater_on && !on {
+            self.heater_settling = self.heater_settle_reads;
+        }
+        self.heater_on = on;
+        self.heater_used |= on;
+    }
+
+    // Switch off the heater left on by a heater pulse dropped before it completed
+    pub(crate) async fn end_cancelled_pulse(&mut self) -> Result<(), Hts221Error<I::Error>> {
+        if self.heater_on {
+            let result = self.set_heater(false).await;
+            self.track(result)?;
+        }
+        Ok(())
+    }
+
+    // Account a read against the heater policy, counting it towards settling
+    pub(crate) fn heater_check(&mut self) -> Result<(), Hts221Error<I::Error>> {
+        if !self.heater_active() {
+            return Ok(());
+        }
+        self.heater_settling = self.heater_settling.saturating_sub(1);
+        self.heater_used = true;
+        match self.heater_policy {
+            HeaterPolicy::Flag => Ok(()),
+            HeaterPolicy::Refuse | HeaterPolicy::Discard => Err(Hts221Error::HeaterActive),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn cancelled_pulse_switches_heater_off() {
+        let mut hts221 = initialized();
+        let mut stall = Stall;
+        let mut pulse = Box::pin(hts221.heater_pulse(&mut stall, 1000));
+        assert!(poll_once(&mut pulse).is_pending());
+        drop(pulse);
+        assert!(sensor(&mut hts221).heater_on());
+
+        block_on(hts221.status()).unwrap();
+        assert!(!sensor(&mut hts221).heater_on());
+        assert!(!hts221.heater_active());
+    }
+
+    #[test]
+    fn refuses_reads_until_settled() {
+        let mut hts221 = initialized();
+        hts221.set_heater_policy(HeaterPolicy::Refuse, 2);
+        block_on(hts221.heater_pulse(&mut NoDelay::default(), 100)).unwrap();
+        assert!(hts221.heater_active());
+        for _ in 0..2 {
+            assert!(matches!(
+                block_on(hts221.read()),
+                Err(Hts221Error::HeaterActive)
+            ));
+        }
+        assert!(block_on(hts221.read()).is_ok());
+    }
+
+    #[test]
+    fn discards_samples_until_settled() {
+        let mut hts221 = initialized();
+        hts221.set_heater_policy(HeaterPolicy::Discard, 2);
+        block_on(hts221.heater_pulse(&mut NoDelay::default(), 100)).unwrap();
+        let mut samples = hts221.samples(NoDelay::default());
+        assert!(block_on(samples.next_sample()).is_ok());
+        assert!(!hts221.heater_active());
+    }
+}
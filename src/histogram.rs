@@ -0,0 +1,145 @@
+use super::{Celsius, SensorAcquisition};
+
+/// Histogram of values with `N` equally sized bins over a configured range.
+///
+/// Values outside of the range are counted separately, so the distribution of long-running
+/// measurements can be reported without keeping the raw values.
+#[derive(Debug, Clone)]
+pub struct Histogram<const N: usize> {
+    min: f32,
+    max: f32,
+    bins: [u32; N],
+    below: u32,
+    above: u32,
+}
+
+impl<const N: usize> Histogram<N> {
+    /// Create an empty histogram over the range from `min` to `max`.
+    pub fn new(min: f32, max: f32) -> Self {
+        Self {
+            min,
+            max,
+            bins: [0; N],
+            below: 0,
+            above: 0,
+        }
+    }
+
+    /// Count a value.
+    pub fn add(&mut self, value: f32) {
+        if value.is_nan() || value < self.min {
+            self.below = self.below.saturating_add(1);
+        } else if value > self.max || N == 0 {
+            self.above = self.above.saturating_add(1);
+        } else {
+            let position = (value - self.min) / (self.max - self.min) * N as f32;
+            let bin = (position as usize).min(N - 1);
+            self.bins[bin] = self.bins[bin].saturating_add(1);
+        }
+    }
+
+    /// Counts of the bins, from the lowest to the highest values.
+    pub fn bins(&self) -> &[u32; N] {
+        &self.bins
+    }
+
+    /// Range of values counted in the given bin.
+    pub fn bin_range(&self, bin: usize) -> (f32, f32) {
+        let width = (self.max - self.min) / N as f32;
+        (
+            self.min + width * bin as f32,
+            self.min + width * (bin + 1) as f32,
+        )
+    }
+
+    /// Number of values below the range.
+    pub fn below(&self) -> u32 {
+        self.below
+    }
+
+    /// Number of values above the range.
+    pub fn above(&self) -> u32 {
+        self.above
+    }
+
+    /// Total number of values counted, including those outside of the range.
+    pub fn count(&self) -> u32 {
+        self.bins
+            .iter()
+            .fold(self.below.saturating_add(self.above), |sum, bin| {
+                sum.saturating_add(*bin)
+            })
+    }
+
+    /// Clear all counts.
+    pub fn reset(&mut self) {
+        self.bins = [0; N];
+        self.below = 0;
+        self.above = 0;
+    }
+}
+
+/// Histograms of temperature and relative humidity, with `T` and `H` bins respectively.
+#[derive(Debug, Clone)]
+pub struct AcquisitionHistogram<const T: usize, const H: usize> {
+    /// Histogram of temperatures in celsius.
+    pub temperature: Histogram<T>,
+    /// Histogram of relative humidity.
+    pub relative_humidity: Histogram<H>,
+}
+
+impl<const T: usize, const H: usize> AcquisitionHistogram<T, H> {
+    /// Create empty histograms over the given temperature range and the full relative
+    /// humidity range.
+    pub fn new(temperature_min: f32, temperature_max: f32) -> Self {
+        Self {
+            temperature: Histogram::new(temperature_min, temperature_max),
+            relative_humidity: Histogram::new(0.0, 100.0),
+        }
+    }
+
+    /// Count the values of an acquisition.
+    pub fn add(&mut self, acquisition: &SensorAcquisition<Celsius>) {
+        self.temperature.add(acquisition.temperature.value);
+        self.relative_humidity.add(acquisition.relative_humidity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_values_into_bins() {
+        let mut histogram = Histogram::<4>::new(0.0, 40.0);
+        for value in [0.0, 9.9, 10.0, 25.0, 40.0, -0.1, 40.1, f32::NAN] {
+            histogram.add(value);
+        }
+        assert_eq!(histogram.bins(), &[2, 1, 1, 1]);
+        assert_eq!(histogram.below(), 2);
+        assert_eq!(histogram.above(), 1);
+        assert_eq!(histogram.count(), 8);
+        assert_eq!(histogram.bin_range(1), (10.0, 20.0));
+
+        histogram.reset();
+        assert_eq!(histogram.count(), 0);
+    }
+
+    #[test]
+    fn counts_values_without_bins() {
+        let mut histogram = Histogram::<0>::new(0.0, 40.0);
+        histogram.add(20.0);
+        assert_eq!(histogram.above(), 1);
+    }
+
+    #[test]
+    fn counts_acquisitions() {
+        let mut histogram = AcquisitionHistogram::<4, 10>::new(-20.0, 60.0);
+        histogram.add(&SensorAcquisition {
+            temperature: 25.0.into(),
+            relative_humidity: 55.0,
+        });
+        assert_eq!(histogram.temperature.bins(), &[0, 0, 1, 0]);
+        assert_eq!(histogram.relative_humidity.bins()[5], 1);
+    }
+}
@@ -0,0 +1,147 @@
+use super::{Hts221, Interface};
+use embedded_hal_async::i2c::ErrorKind;
+
+/// Bus traffic counted by an [`InstrumentedInterface`].
+///
+/// Bytes are the payload of the transactions, register addresses included and the I2C
+/// device address excluded. Failed transactions are counted too, as they occupy the bus
+/// all the same.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BusUsage {
+    /// Number of transactions.
+    pub transactions: u32,
+    /// Number of bytes written to the sensor.
+    pub bytes_written: u32,
+    /// Number of bytes read from the sensor.
+    pub bytes_read: u32,
+}
+
+impl BusUsage {
+    fn add(&mut self, written: usize, read: usize) {
+        self.transactions = self.transactions.saturating_add(1);
+        self.bytes_written = self.bytes_written.saturating_add(written as u32);
+        self.bytes_read = self.bytes_read.saturating_add(read as u32);
+    }
+}
+
+/// Wrapper around an interface counting the transactions and bytes on the bus.
+///
+/// Take the usage before and after a driver operation to measure its cost, for example
+/// with [`Hts221::take_bus_usage`].
+pub struct InstrumentedInterface<I> {
+    interface: I,
+    usage: BusUsage,
+}
+
+impl<I> InstrumentedInterface<I>
+where
+    I: Interface,
+{
+    /// Wrap an interface, starting from zero counts.
+    pub fn new(interface: I) -> Self {
+        Self {
+            interface,
+            usage: BusUsage::default(),
+        }
+    }
+
+    /// Bus traffic counted so far.
+    pub fn usage(&self) -> BusUsage {
+        self.usage
+    }
+
+    /// Bus traffic counted so far, restarting from zero.
+    pub fn take_usage(&mut self) -> BusUsage {
+        core::mem::take(&mut self.usage)
+    }
+
+    /// Release the wrapped interface.
+    pub fn release(self) -> I {
+        self.interface
+    }
+}
+
+impl<I> Interface for InstrumentedInterface<I>
+where
+    I: Interface,
+{
+    type Error = I::Error;
+
+    async fn read_registers(&mut self, start: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.usage.add(1, buf.len());
+        self.interface.read_registers(start, buf).await
+    }
+
+    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), Self::Error> {
+        self.usage.add(2, 0);
+        self.interface.write_register(register, value).await
+    }
+
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        I::error_kind(error)
+    }
+
+    fn is_timeout(error: &Self::Error) -> bool {
+        I::is_timeout(error)
+    }
+}
+
+impl<I> Hts221<InstrumentedInterface<I>>
+where
+    I: Interface,
+{
+    /// Bus traffic of the driver so far.
+    pub fn bus_usage(&self) -> BusUsage {
+        self.interface.interface.usage()
+    }
+
+    /// Bus traffic of the driver since the previous call, restarting from zero, so calling
+    /// it around an operation gives the cost of that operation.
+    pub fn take_bus_usage(&mut self) -> BusUsage {
+        self.interface.interface.take_usage()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn counts_transactions_and_bytes() {
+        let mut interface = InstrumentedInterface::new(FakeSensor::new());
+        let mut buf = [0; 4];
+        block_on(interface.read_registers(CALIBRATION, &mut buf)).unwrap();
+        block_on(interface.write_register(CTRL_REG1, 0x81)).unwrap();
+        assert_eq!(
+            interface.take_usage(),
+            BusUsage {
+                transactions: 2,
+                bytes_written: 3,
+                bytes_read: 4,
+            }
+        );
+        assert_eq!(interface.usage(), BusUsage::default());
+    }
+
+    #[test]
+    fn counts_failed_transactions() {
+        let mut sensor = FakeSensor::new();
+        sensor.fail = 1;
+        let mut interface = InstrumentedInterface::new(sensor);
+        assert!(block_on(interface.write_register(CTRL_REG1, 0x81)).is_err());
+        assert_eq!(interface.usage().transactions, 1);
+    }
+
+    #[test]
+    fn measures_driver_operations() {
+        let mut hts221 = Hts221::with_interface(InstrumentedInterface::new(FakeSensor::new()));
+        block_on(hts221.initialize()).unwrap();
+        hts221.take_bus_usage();
+        block_on(hts221.read()).unwrap();
+        let usage = hts221.bus_usage();
+        assert!(usage.transactions > 0);
+        assert_eq!(usage.bytes_written, usage.transactions);
+    }
+}
@@ -0,0 +1,127 @@
+use core::fmt::{Formatter, LowerHex, UpperHex};
+use embedded_hal_async::i2c::*;
+
+const ADDR: u8 = 0x5F;
+
+// Setting the MSB of the register address enables auto-increment for multi-byte reads
+const AUTO_INCREMENT: u8 = 0x80;
+
+/// Register level access to the sensor, separating the transport from the driver logic.
+///
+/// Implement this trait to use the driver over another transport, or to wrap an existing
+/// interface with middleware. The typed registers and the driver only see register
+/// addresses and values, so middleware such as caching, tracing, fault injection or
+/// address translation wraps any interface without knowing about the transport, as
+/// [`TimeoutInterface`](super::TimeoutInterface) does. Transport details like the
+/// auto-increment bit of [`I2cInterface`] stay in the innermost interface.
+///
+/// Also exported as [`RegisterInterface`].
+pub trait Interface {
+    /// Error type of the transport.
+    type Error;
+
+    /// Read consecutive registers starting at `start` into `buf`.
+    async fn read_registers(&mut self, start: u8, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Write a single register.
+    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), Self::Error>;
+
+    /// Classify a transport error, for health reporting.
+    fn error_kind(_error: &Self::Error) -> ErrorKind {
+        ErrorKind::Other
+    }
+
+    /// Whether a transport error means the operation timed out, reported by the driver as
+    /// [`Hts221Error::Timeout`](super::Hts221Error::Timeout).
+    fn is_timeout(_error: &Self::Error) -> bool {
+        false
+    }
+}
+
+/// Name of [`Interface`] when used as the register level extension point for middleware.
+pub use Interface as RegisterInterface;
+
+/// Interface to the sensor over I2C from embedded-hal-async.
+pub struct I2cInterface<I> {
+    i2c: I,
+    address: I2cAddress,
+}
+
+impl<I> I2cInterface<I>
+where
+    I: I2c<SevenBitAddress>,
+{
+    /// Create an interface using a given I2C peripheral.
+    pub fn new(i2c: I) -> Self {
+        Self {
+            i2c,
+            address: I2cAddress::new(ADDR),
+        }
+    }
+
+    /// Release the I2C peripheral.
+    pub fn release(self) -> I {
+        self.i2c
+    }
+}
+
+impl<I> Interface for I2cInterface<I>
+where
+    I: I2c<SevenBitAddress>,
+{
+    type Error = I::Error;
+
+    async fn read_registers(&mut self, start: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let start = if buf.len() > 1 {
+            start | AUTO_INCREMENT
+        } else {
+            start & !AUTO_INCREMENT
+        };
+        self.i2c
+            .write_read(self.address.into(), &[start], buf)
+            .await
+    }
+
+    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), Self::Error> {
+        self.i2c
+            .write(self.address.into(), &[register, value])
+            .await
+    }
+
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        error.kind()
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub(crate) struct I2cAddress(u8);
+
+impl I2cAddress {
+    pub fn new(val: u8) -> Self {
+        Self(val)
+    }
+}
+
+impl Into<u8> for I2cAddress {
+    fn into(self) -> u8 {
+        self.0
+    }
+}
+
+impl Into<I2cAddress> for u8 {
+    fn into(self) -> I2cAddress {
+        I2cAddress::new(self)
+    }
+}
+
+impl LowerHex for I2cAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl UpperHex for I2cAddress {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        UpperHex::fmt(&self.0, f)
+    }
+}
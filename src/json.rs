@@ -0,0 +1,64 @@
+use super::{round_to, Celsius, SensorAcquisition, HAS_HUMIDITY, HAS_TEMPERATURE};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+impl SensorAcquisition<Celsius> {
+    /// Encode the acquisition as JSON, such as `{"temp_c":23.4,"rh":45.1}`, into `buf`.
+    ///
+    /// Values are rounded to a tenth, and a channel compiled out with the `temperature-only`
+    /// or `humidity-only` feature is omitted. Returns the number of bytes written.
+    pub fn to_json(&self, buf: &mut [u8]) -> Result<usize, serde_json_core::ser::Error> {
+        serde_json_core::to_slice(self, buf)
+    }
+}
+
+impl Serialize for SensorAcquisition<Celsius> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = HAS_TEMPERATURE as usize + HAS_HUMIDITY as usize;
+        let mut reading = serializer.serialize_struct("SensorAcquisition", fields)?;
+        if HAS_TEMPERATURE {
+            reading.serialize_field("temp_c", &tenths(self.temperature.value))?;
+        }
+        if HAS_HUMIDITY {
+            reading.serialize_field("rh", &tenths(self.relative_humidity))?;
+        }
+        reading.end()
+    }
+}
+
+// Dividing the rounded value keeps the shortest decimal representation, e.g. `23.4`.
+fn tenths(value: f32) -> f32 {
+    round_to(value * 10.0, 1.0) / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(temperature: f32, relative_humidity: f32) -> std::string::String {
+        let mut buf = [0; 64];
+        let acquisition = SensorAcquisition {
+            temperature: temperature.into(),
+            relative_humidity,
+        };
+        let len = acquisition.to_json(&mut buf).unwrap();
+        std::string::String::from_utf8(buf[..len].to_vec()).unwrap()
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+    fn encodes_both_channels() {
+        assert_eq!(json(23.44, 45.06), r#"{"temp_c":23.4,"rh":45.1}"#);
+    }
+
+    #[test]
+    #[cfg(feature = "temperature-only")]
+    fn omits_humidity() {
+        assert_eq!(json(23.44, f32::NAN), r#"{"temp_c":23.4}"#);
+    }
+
+    #[test]
+    #[cfg(feature = "humidity-only")]
+    fn omits_temperature() {
+        assert_eq!(json(f32::NAN, 45.06), r#"{"rh":45.1}"#);
+    }
+}
@@ -1,157 +1,722 @@
-#![no_std]
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
 #![feature(type_alias_impl_trait)]
+// The traits are for single-threaded executors, their futures need not be Send
+#![allow(async_fn_in_trait)]
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
 
-use core::fmt::{LowerHex, UpperHex};
 use core::{
-    fmt::{Debug, Display, Formatter},
+    fmt::{Debug, Display, Formatter, Write},
     marker::PhantomData,
     ops::{Add, Div, Sub},
 };
-use embedded_hal_async::i2c::*;
+use embedded_hal_async::{delay::DelayUs, i2c::*};
+#[cfg(not(feature = "temperature-only"))]
+use register::h_out::Hout;
+#[cfg(not(feature = "humidity-only"))]
+use register::t_out::Tout;
 use register::{
     calibration::*,
-    ctrl1::{BlockDataUpdate, Ctrl1, OutputDataRate},
+    ctrl1::Ctrl1,
     ctrl2::Ctrl2,
     ctrl3::Ctrl3,
-    h_out::Hout,
-    status::Status,
-    t_out::Tout,
+    who_am_i::{WhoAmI, HTS221_ID},
 };
 
+#[cfg(all(feature = "temperature-only", feature = "humidity-only"))]
+compile_error!("features `temperature-only` and `humidity-only` are mutually exclusive");
+
+mod accuracy;
+mod average;
+mod config;
+mod control;
+mod csv;
+mod deadband;
+#[cfg(any(feature = "libm", feature = "micromath"))]
+mod derived;
+mod drift;
+mod event;
+#[cfg(any(test, feature = "nb"))]
+mod executor;
+mod heater;
+mod histogram;
+#[cfg(feature = "instrumentation")]
+mod instrumentation;
+mod interface;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "fugit")]
+mod log;
+#[cfg(any(feature = "libm", feature = "micromath"))]
+mod math;
+#[cfg(feature = "mock")]
+mod mock;
+mod oneshot;
+mod pingpong;
+#[cfg(feature = "nb")]
+mod polling;
+#[cfg(feature = "power")]
+mod power;
+#[cfg(feature = "std")]
+mod prometheus;
+mod range;
+mod recovery;
+mod redundant;
+#[cfg(feature = "registers")]
+pub mod register;
+#[cfg(not(feature = "registers"))]
 mod register;
+mod reset;
+mod samples;
+mod schedule;
+mod shared;
+#[cfg(feature = "stats")]
+mod stats;
+mod telemetry;
+#[cfg(test)]
+mod testing;
+mod threshold;
+mod timeout;
+mod trace;
 
-const ADDR: u8 = 0x5F;
+pub use accuracy::*;
+pub use average::*;
+pub use config::*;
+pub use control::*;
+pub use csv::*;
+pub use deadband::*;
+#[cfg(any(feature = "libm", feature = "micromath"))]
+pub use derived::*;
+pub use drift::*;
+pub use event::*;
+pub use heater::*;
+pub use histogram::*;
+#[cfg(feature = "instrumentation")]
+pub use instrumentation::*;
+pub use interface::*;
+#[cfg(feature = "fugit")]
+pub use log::*;
+#[cfg(feature = "mock")]
+pub use mock::*;
+pub use oneshot::*;
+pub use pingpong::*;
+#[cfg(feature = "power")]
+pub use power::*;
+#[cfg(feature = "std")]
+pub use prometheus::*;
+pub use range::*;
+pub use recovery::*;
+pub use redundant::*;
+pub use register::{
+    av_conf::AvConf,
+    calibration::RawCalibration,
+    ctrl1::{OutputDataRate, PowerMode},
+    ctrl3::{ActiveState, ReadyMode},
+    status::Status,
+};
+pub use samples::*;
+pub use schedule::*;
+pub use shared::*;
+#[cfg(feature = "stats")]
+pub use stats::*;
+pub use telemetry::*;
+pub use threshold::*;
+pub use timeout::*;
+pub use trace::*;
 
 /// Error returned by Hts221 driver
+#[derive(Debug)]
 pub enum Hts221Error<E> {
-    /// Error from I2C.
+    /// Error from the I2C bus, or the transport of the interface.
     I2c(E),
     /// Attempting to read before calibration.
     NotCalibrated,
     /// Not the expected sensor device
-    InvalidSensor,
+    InvalidSensor {
+        /// Identification value read from the device.
+        found: u8,
+        /// Identification value expected for the HTS221.
+        expected: u8,
+    },
+    /// The calibration data stored on the sensor is not usable.
+    BadCalibration,
+    /// A bus operation did not complete in time, see [`TimeoutInterface`], or the sensor
+    /// did not report a new sample within a few periods of the output data rate.
+    Timeout,
+    /// A configuration register did not read back the value written to it, see
+    /// [`Hts221::set_verify_config`].
+    ConfigVerifyFailed {
+        /// Address of the register.
+        register: u8,
+        /// Value written to the register.
+        written: u8,
+        /// Value read back from the register.
+        read: u8,
+    },
+    /// The values would be affected by the heater, see [`Hts221::set_heater_policy`].
+    HeaterActive,
+    /// The operation needs a channel compiled out with the `temperature-only` or
+    /// `humidity-only` feature.
+    ChannelUnavailable,
+}
+
+impl<E: Error> Hts221Error<E> {
+    /// Whether the error is transient, so the operation may succeed when retried.
+    ///
+    /// Bus level errors such as a missing acknowledge or lost arbitration are transient,
+    /// while a wrong device or bad calibration data will not go away by retrying.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Hts221Error::I2c(e) => matches!(
+                e.kind(),
+                ErrorKind::NoAcknowledge(_)
+                    | ErrorKind::ArbitrationLoss
+                    | ErrorKind::Bus
+                    | ErrorKind::Overrun
+            ),
+            Hts221Error::Timeout | Hts221Error::HeaterActive => true,
+            Hts221Error::NotCalibrated
+            | Hts221Error::InvalidSensor { .. }
+            | Hts221Error::BadCalibration
+            | Hts221Error::ConfigVerifyFailed { .. }
+            | Hts221Error::ChannelUnavailable => false,
+        }
+    }
 }
 
-/// An instance of the HTS221 driver using I2C transport from embedded-hal-async.
+// Interval between status polls while waiting for a one-shot acquisition
+const ONE_SHOT_POLL_MS: u32 = 10;
+
+// Polls of a one-shot acquisition before giving up on it
+const ONE_SHOT_ATTEMPTS: u32 = 50;
+
+// Output data rate periods to wait for a new sample before giving up on it
+const READY_PERIODS: u32 = 4;
+
+// Status reads while emptying the output registers during initialization
+const DRAIN_ATTEMPTS: u32 = 8;
+
+/// Meaningful resolution of temperature values, in degrees.
+///
+/// Finer digits are dominated by noise given the accuracy of the sensor.
+pub const TEMPERATURE_RESOLUTION: f32 = 0.1;
+
+/// Meaningful resolution of relative humidity values, in percentage points.
+pub const HUMIDITY_RESOLUTION: f32 = 0.5;
+
+/// Default maximum time the heater may stay on, in milliseconds.
+pub const DEFAULT_MAX_HEATER_TIME_MS: u32 = 10_000;
+
+/// An instance of the HTS221 driver, using I2C transport from embedded-hal-async by default.
 pub struct Hts221<I>
 where
-    I: I2c<SevenBitAddress> + 'static,
-    <I as ErrorType>::Error: Send,
+    I: Interface,
 {
-    i2c: I,
-    address: I2cAddress,
+    interface: Tracked<I>,
     calibration: Option<Calibration>,
+    max_heater_time_ms: u32,
+    error_hook: Option<ErrorHook>,
+    verify_config: bool,
+    config: Config,
+    expected_id: Option<u8>,
+    reset_check_interval: u32,
+    reads_since_check: u32,
+    heater_used: bool,
+    heater_on: bool,
+    heater_policy: HeaterPolicy,
+    heater_settle_reads: u32,
+    heater_settling: u32,
+    outlier_steps: Option<(f32, f32)>,
+    previous: Option<SensorAcquisition<Celsius>>,
+    rejected: Option<SensorAcquisition<Celsius>>,
+    #[cfg(feature = "stats")]
+    stats: Stats,
 }
 
-impl<I> Hts221<I>
+impl<I> Hts221<I2cInterface<I>>
 where
-    I: I2c<SevenBitAddress> + 'static,
-    <I as ErrorType>::Error: Send,
+    I: I2c<SevenBitAddress>,
 {
     /// Create a new instance of the driver using a given I2C peripheral.
     pub fn new(i2c: I) -> Self {
+        Self::with_interface(I2cInterface::new(i2c))
+    }
+}
+
+impl<I> Hts221<I>
+where
+    I: Interface,
+{
+    /// Create a new instance of the driver using a given interface.
+    pub fn with_interface(interface: I) -> Self {
         Self {
-            i2c,
-            address: I2cAddress(ADDR),
+            interface: Tracked::new(interface),
             calibration: None,
+            max_heater_time_ms: DEFAULT_MAX_HEATER_TIME_MS,
+            error_hook: None,
+            verify_config: false,
+            config: Config::default(),
+            expected_id: Some(HTS221_ID),
+            reset_check_interval: 0,
+            reads_since_check: 0,
+            heater_used: false,
+            heater_on: false,
+            heater_policy: HeaterPolicy::Flag,
+            heater_settle_reads: 0,
+            heater_settling: 0,
+            outlier_steps: None,
+            previous: None,
+            rejected: None,
+            #[cfg(feature = "stats")]
+            stats: Stats::default(),
         }
     }
 
+    /// Release the interface.
+    pub fn release(self) -> I {
+        self.interface.interface
+    }
+
+    /// Register a callback invoked on every driver error, with the kind of error and the
+    /// register being accessed.
+    pub fn set_error_hook(&mut self, hook: ErrorHook) {
+        self.error_hook.replace(hook);
+    }
+
     /// Initialize the driver. Must be run before reading sensor values.
     pub async fn initialize(&mut self) -> Result<(), Hts221Error<I::Error>> {
-        Ctrl2::modify(self.address, &mut self.i2c, |reg| {
-            reg.boot();
-        })
-        .await?;
+        self.end_cancelled_pulse().await?;
+        #[cfg(feature = "stats")]
+        if self.calibration.is_some() {
+            self.stats.reinits = self.stats.reinits.saturating_add(1);
+        }
+        let result = self.configure().await;
+        self.track(result)
+    }
 
-        Ctrl1::modify(self.address, &mut self.i2c, |reg| {
-            reg.power_active()
-                .output_data_rate(OutputDataRate::Hz1)
-                .block_data_update(BlockDataUpdate::MsbLsbReading);
-        })
-        .await?;
+    async fn configure(&mut self) -> Result<(), Hts221Error<I::Error>> {
+        let who_am_i: u8 = WhoAmI::read(&mut self.interface).await?;
+        match self.expected_id {
+            Some(expected) if who_am_i != expected => {
+                return Err(Hts221Error::InvalidSensor {
+                    found: who_am_i,
+                    expected,
+                });
+            }
+            _ => {}
+        }
 
-        Ctrl3::modify(self.address, &mut self.i2c, |reg| {
-            reg.enable(true);
+        Ctrl2::modify(&mut self.interface, |reg| {
+            reg.boot();
         })
         .await?;
 
-        loop {
-            // Ensure status is emptied
-            if let Ok(status) = Status::read(self.address, &mut self.i2c).await {
-                if !status.any_available() {
+        let config = self.config;
+        self.write_ctrl1(&config).await?;
+        self.write_ctrl3(&config).await?;
+        self.write_av_conf(&config).await?;
+
+        // Ensure status is emptied, giving up on a status register which cannot be read
+        let mut drained = Ok(());
+        for _ in 0..DRAIN_ATTEMPTS {
+            match Status::read(&mut self.interface).await {
+                Ok(status) if !status.any_available() => {
+                    drained = Ok(());
                     break;
                 }
+                Ok(_) => drained = Ok(()),
+                Err(e) => {
+                    #[cfg(feature = "stats")]
+                    self.stats.retry::<I>(&e);
+                    drained = Err(e);
+                }
             }
-            Hout::read(self.address, &mut self.i2c).await?;
-            Tout::read(self.address, &mut self.i2c).await?;
+            #[cfg(not(feature = "temperature-only"))]
+            Hout::read(&mut self.interface).await?;
+            #[cfg(not(feature = "humidity-only"))]
+            Tout::read(&mut self.interface).await?;
         }
+        drained?;
 
-        self.calibration
-            .replace(Calibration::read(self.address, &mut self.i2c).await?);
+        let calibration = Calibration::read(&mut self.interface).await?;
+        if !calibration.is_valid() {
+            return Err(Hts221Error::BadCalibration);
+        }
+        self.calibration.replace(calibration);
         Ok(())
     }
 
     /// Read sensor values from driver.
     pub async fn read(&mut self) -> Result<SensorAcquisition<Celsius>, Hts221Error<I::Error>> {
-        if let Some(calibration) = &self.calibration {
-            let t_out = Tout::read(self.address, &mut self.i2c).await? as i16;
-            let temperature = calibration.calibrated_temperature(t_out);
+        self.end_cancelled_pulse().await?;
+        self.reset_check_due().await?;
+        let result = self.heater_check();
+        self.track(result)?;
+        let result = self.acquire().await;
+        self.track_read(result)
+    }
+
+    /// Read only the temperature value from driver.
+    #[cfg(not(feature = "humidity-only"))]
+    pub async fn read_temperature(
+        &mut self,
+    ) -> Result<Temperature<Celsius>, Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        self.reset_check_due().await?;
+        let result = self.heater_check();
+        self.track(result)?;
+        let result = self.acquire_temperature().await;
+        self.track_read(result)
+    }
+
+    /// Read only the relative humidity value from driver.
+    #[cfg(not(feature = "temperature-only"))]
+    pub async fn read_humidity(&mut self) -> Result<f32, Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        self.reset_check_due().await?;
+        let result = self.heater_check();
+        self.track(result)?;
+        let result = self.acquire_humidity().await;
+        self.track_read(result)
+    }
+
+    /// Fill `samples` with consecutive acquisitions at the configured output data rate.
+    ///
+    /// The status register is polled using `delay` until a new sample is ready; in one-shot
+    /// mode an acquisition is started for every sample. Fails with [`Hts221Error::Timeout`]
+    /// if the sensor stops reporting new samples.
+    pub async fn read_into<D: DelayUs>(
+        &mut self,
+        samples: &mut [SensorAcquisition<Celsius>],
+        delay: &mut D,
+    ) -> Result<(), Hts221Error<I::Error>> {
+        let odr = self.current_odr().await?;
+        for sample in samples.iter_mut() {
+            *sample = self.wait_for_sample(odr, delay).await?;
+        }
+        Ok(())
+    }
 
-            let h_out = Hout::read(self.address, &mut self.i2c).await?;
-            let relative_humidity = calibration.calibrated_humidity(h_out);
+    /// Iterate over consecutive acquisitions at the configured output data rate, using
+    /// `delay` to wait for each sample to be ready.
+    pub fn samples<D: DelayUs>(&mut self, delay: D) -> Samples<'_, I, D> {
+        Samples::new(self, delay)
+    }
 
-            Ok(SensorAcquisition {
-                temperature,
-                relative_humidity,
+    pub(crate) async fn wait_for_sample<D: DelayUs>(
+        &mut self,
+        odr: OutputDataRate,
+        delay: &mut D,
+    ) -> Result<SensorAcquisition<Celsius>, Hts221Error<I::Error>> {
+        if self.calibration.is_none() {
+            return Err(Hts221Error::NotCalibrated);
+        }
+        self.end_cancelled_pulse().await?;
+        loop {
+            let result = self.wait_until_ready(odr, delay).await;
+            self.track(result)?;
+            if self.heater_policy != HeaterPolicy::Discard || !self.heater_active() {
+                return self.read().await;
+            }
+            // Read the outputs anyway, so the sample is consumed
+            self.heater_check().ok();
+            let result = self.acquire().await;
+            self.track(result)?;
+        }
+    }
+
+    async fn wait_until_ready<D: DelayUs>(
+        &mut self,
+        odr: OutputDataRate,
+        delay: &mut D,
+    ) -> Result<(), Hts221Error<I::Error>> {
+        if odr == OutputDataRate::OneShot {
+            Ctrl2::modify(&mut self.interface, |reg| {
+                reg.enable_one_shot();
             })
+            .await?;
+        }
+        // Poll four times per period, so a sample is picked up soon after it is ready
+        let (poll_ms, attempts) = match odr.period_ms() {
+            Some(period) => (period / 4, 4 * READY_PERIODS),
+            None => (ONE_SHOT_POLL_MS, ONE_SHOT_ATTEMPTS),
+        };
+        for _ in 0..attempts {
+            let status = Status::read(&mut self.interface).await?;
+            if sample_available(&status) {
+                return Ok(());
+            }
+            delay.delay_ms(poll_ms).await;
+        }
+        Err(Hts221Error::Timeout)
+    }
+
+    /// Set the identification value expected in the WHO_AM_I register during
+    /// [`initialize`](Self::initialize), or `None` to skip the check.
+    ///
+    /// Some compatible modules report a different value than the HTS221.
+    pub fn set_expected_id(&mut self, expected_id: Option<u8>) {
+        self.expected_id = expected_id;
+    }
+
+    /// Read back every control register written during [`initialize`](Self::initialize),
+    /// failing with [`Hts221Error::ConfigVerifyFailed`] on a mismatch.
+    ///
+    /// This catches marginal bus issues at bring-up, at the cost of extra transactions.
+    pub fn set_verify_config(&mut self, verify_config: bool) {
+        self.verify_config = verify_config;
+    }
+
+    /// Set the maximum time the heater may stay on during a [`heater_pulse`](Self::heater_pulse).
+    pub fn set_max_heater_time(&mut self, max_heater_time_ms: u32) {
+        self.max_heater_time_ms = max_heater_time_ms;
+    }
+
+    /// Switch the internal heater on for the given duration, then off again.
+    ///
+    /// The duration is limited to the configured maximum heater time, so the sensing element
+    /// is not damaged. If the future is dropped before it completes, the heater is switched
+    /// off at the start of the next driver operation.
+    pub async fn heater_pulse<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+        duration_ms: u32,
+    ) -> Result<(), Hts221Error<I::Error>> {
+        let duration_ms = duration_ms.min(self.max_heater_time_ms);
+        let result = self.set_heater(true).await;
+        self.track(result)?;
+        delay.delay_ms(duration_ms).await;
+        let result = self.set_heater(false).await;
+        self.track(result)
+    }
+
+    async fn set_heater(&mut self, on: bool) -> Result<(), Hts221Error<I::Error>> {
+        Ctrl2::modify(&mut self.interface, |reg| {
+            reg.heater(on);
+        })
+        .await?;
+        self.heater_switched(on);
+        Ok(())
+    }
+
+    /// Read the status register, indicating which new samples are available.
+    pub async fn status(&mut self) -> Result<Status, Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = Status::read(&mut self.interface)
+            .await
+            .map_err(Hts221Error::I2c);
+        self.track(result)
+    }
+
+    /// Read back the output data rate currently configured on the device.
+    pub async fn current_odr(&mut self) -> Result<OutputDataRate, Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = Ctrl1::read(&mut self.interface)
+            .await
+            .map(|reg| reg.current_output_data_rate())
+            .map_err(Hts221Error::I2c);
+        self.track(result)
+    }
+
+    /// Read back the power mode currently configured on the device.
+    pub async fn power_mode(&mut self) -> Result<PowerMode, Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = Ctrl1::read(&mut self.interface)
+            .await
+            .map(|reg| reg.power_mode())
+            .map_err(Hts221Error::I2c);
+        self.track(result)
+    }
+
+    /// Read back the data ready signal configuration of the device.
+    pub async fn data_ready_config(&mut self) -> Result<DataReadyConfig, Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = Ctrl3::read(&mut self.interface)
+            .await
+            .map(|reg| DataReadyConfig {
+                enabled: reg.enable,
+                active_state: reg.active,
+                mode: reg.mode,
+            })
+            .map_err(Hts221Error::I2c);
+        self.track(result)
+    }
+
+    /// Read consecutive registers starting at `start` into `buf`.
+    ///
+    /// The auto-increment bit is set automatically when reading more than one byte, so
+    /// `start` is the plain register address from the datasheet.
+    pub async fn read_regs(
+        &mut self,
+        start: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = self
+            .interface
+            .read_registers(start, buf)
+            .await
+            .map_err(Hts221Error::I2c);
+        self.track(result)
+    }
+
+    /// Run a self-check of the sensor, verifying its identity, status and calibration data.
+    pub async fn health(&mut self) -> Result<HealthReport, Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = self.check_health().await;
+        self.track(result)
+    }
+
+    async fn check_health(&mut self) -> Result<HealthReport, Hts221Error<I::Error>> {
+        let who_am_i: u8 = WhoAmI::read(&mut self.interface).await?;
+        let status = Status::read(&mut self.interface).await?;
+        let calibration = Calibration::read(&mut self.interface).await?;
+
+        Ok(HealthReport {
+            who_am_i,
+            sensor_valid: self
+                .expected_id
+                .map_or(true, |expected| who_am_i == expected),
+            temperature_available: status.temperature_available(),
+            humidity_available: status.humidity_available(),
+            calibration_valid: calibration.is_valid(),
+        })
+    }
+
+    /// Counters maintained by the driver, for reporting device health.
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    // A channel compiled out is reported as NaN
+    async fn acquire(&mut self) -> Result<SensorAcquisition<Celsius>, Hts221Error<I::Error>> {
+        #[cfg(not(feature = "humidity-only"))]
+        let temperature = self.acquire_temperature().await?;
+        #[cfg(feature = "humidity-only")]
+        let temperature = self.acquire_calibration().map(|_| f32::NAN.into())?;
+        #[cfg(not(feature = "temperature-only"))]
+        let relative_humidity = self.acquire_humidity().await?;
+        #[cfg(feature = "temperature-only")]
+        let relative_humidity = self.acquire_calibration().map(|_| f32::NAN)?;
+
+        Ok(SensorAcquisition {
+            temperature,
+            relative_humidity,
+        })
+    }
+
+    #[cfg(any(feature = "temperature-only", feature = "humidity-only"))]
+    fn acquire_calibration(&self) -> Result<&Calibration, Hts221Error<I::Error>> {
+        self.calibration.as_ref().ok_or(Hts221Error::NotCalibrated)
+    }
+
+    #[cfg(not(feature = "humidity-only"))]
+    async fn acquire_temperature(&mut self) -> Result<Temperature<Celsius>, Hts221Error<I::Error>> {
+        if let Some(calibration) = &self.calibration {
+            let t_out = Tout::read(&mut self.interface).await? as i16;
+            Ok(calibration.calibrated_temperature(t_out))
         } else {
             Err(Hts221Error::NotCalibrated)
         }
     }
-}
 
-impl<E> From<E> for Hts221Error<E>
-where
-    E: Send,
-{
-    fn from(e: E) -> Hts221Error<E> {
-        Hts221Error::I2c(e)
+    #[cfg(not(feature = "temperature-only"))]
+    async fn acquire_humidity(&mut self) -> Result<f32, Hts221Error<I::Error>> {
+        if let Some(calibration) = &self.calibration {
+            let h_out = Hout::read(&mut self.interface).await?;
+            Ok(calibration.calibrated_humidity(h_out))
+        } else {
+            Err(Hts221Error::NotCalibrated)
+        }
     }
-}
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-pub(crate) struct I2cAddress(u8);
+    fn track<T>(
+        &mut self,
+        result: Result<T, Hts221Error<I::Error>>,
+    ) -> Result<T, Hts221Error<I::Error>> {
+        let result = result.map_err(|e| match e {
+            Hts221Error::I2c(e) if I::is_timeout(&e) => Hts221Error::Timeout,
+            e => e,
+        });
+        if let Err(e) = &result {
+            #[cfg(feature = "stats")]
+            self.stats.error::<I>(e);
+            if let Some(hook) = self.error_hook {
+                hook(&ErrorEvent::new::<I>(e, self.interface.register));
+            }
+        }
+        result
+    }
 
-impl I2cAddress {
-    pub fn new(val: u8) -> Self {
-        Self(val)
+    fn track_read<T>(
+        &mut self,
+        result: Result<T, Hts221Error<I::Error>>,
+    ) -> Result<T, Hts221Error<I::Error>> {
+        #[cfg(feature = "stats")]
+        if result.is_ok() {
+            self.stats.reads = self.stats.reads.saturating_add(1);
+        }
+        self.track(result)
     }
-}
 
-impl Into<u8> for I2cAddress {
-    fn into(self) -> u8 {
-        self.0
+    /// Move the driver into shared state, which can be [`split`](SharedHts221::split) into
+    /// separate temperature and humidity handles.
+    pub fn into_shared(self) -> SharedHts221<I> {
+        SharedHts221::new(self)
     }
 }
 
-impl Into<I2cAddress> for u8 {
-    fn into(self) -> I2cAddress {
-        I2cAddress::new(self)
+impl<E> From<E> for Hts221Error<E> {
+    fn from(e: E) -> Hts221Error<E> {
+        Hts221Error::I2c(e)
     }
 }
 
-impl LowerHex for I2cAddress {
-    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        LowerHex::fmt(&self.0, f)
+/// Configuration of the data ready (DRDY) signal.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DataReadyConfig {
+    /// Whether the data ready signal is enabled on the DRDY pin.
+    pub enabled: bool,
+    /// Active level of the DRDY pin.
+    pub active_state: ActiveState,
+    /// Output type of the DRDY pin.
+    pub mode: ReadyMode,
+}
+
+/// Result of a sensor self-check.
+#[derive(Copy, Clone, Debug)]
+pub struct HealthReport {
+    /// Identification value read from the WHO_AM_I register.
+    pub who_am_i: u8,
+    /// Whether the identification value matches the HTS221.
+    pub sensor_valid: bool,
+    /// Whether a new temperature sample was available.
+    pub temperature_available: bool,
+    /// Whether a new humidity sample was available.
+    pub humidity_available: bool,
+    /// Whether the calibration data stored on the sensor is usable.
+    pub calibration_valid: bool,
+}
+
+impl HealthReport {
+    /// Whether all checks passed.
+    pub fn healthy(&self) -> bool {
+        self.sensor_valid && self.calibration_valid
     }
 }
 
-impl UpperHex for I2cAddress {
-    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        UpperHex::fmt(&self.0, f)
+#[cfg(feature = "defmt")]
+impl defmt::Format for HealthReport {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "HealthReport(who_am_i: {=u8:#x}, sensor_valid: {}, calibration_valid: {})",
+            self.who_am_i,
+            self.sensor_valid,
+            self.calibration_valid
+        );
     }
 }
 
@@ -162,7 +727,7 @@ pub trait TemperatureScale: Send {
 }
 
 /// Discriminant for the _Kelvin_ temperature scale.
-#[derive(Clone)]
+#[derive(Copy, Clone)]
 pub struct Kelvin;
 
 impl TemperatureScale for Kelvin {
@@ -183,7 +748,7 @@ impl defmt::Format for Kelvin {
 }
 
 /// Discriminant for the _Celsius_ temperature scale.
-#[derive(Clone)]
+#[derive(Copy, Clone)]
 pub struct Celsius;
 
 impl Debug for Celsius {
@@ -204,7 +769,7 @@ impl TemperatureScale for Celsius {
 }
 
 /// Discriminant for the _Fahrenheit_ temperature scale.
-#[derive(Clone)]
+#[derive(Copy, Clone)]
 pub struct Fahrenheit;
 
 impl Debug for Fahrenheit {
@@ -241,7 +806,7 @@ impl<S: TemperatureScale> Clone for Temperature<S> {
 
 impl<S: TemperatureScale> Debug for Temperature<S> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        write!(f, "{}°{}", &self.value, S::LETTER)
+        write!(f, "{:?}°{}", Value(self.value), S::LETTER)
     }
 }
 
@@ -254,6 +819,12 @@ impl<S: TemperatureScale> defmt::Format for Temperature<S> {
 
 impl<S: TemperatureScale> Copy for Temperature<S> {}
 
+impl<S: TemperatureScale> PartialEq for Temperature<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
 impl<S: TemperatureScale> Temperature<S> {
     fn new(value: f32) -> Self {
         Self {
@@ -266,6 +837,11 @@ impl<S: TemperatureScale> Temperature<S> {
     pub fn raw_value(&self) -> f32 {
         self.value
     }
+
+    /// Round the value to the nearest multiple of `step`.
+    pub fn round_to(self, step: f32) -> Self {
+        Self::new(round_to(self.value, step))
+    }
 }
 
 impl Temperature<Celsius> {
@@ -321,7 +897,7 @@ impl<S: TemperatureScale> Div<f32> for Temperature<S> {
 
 impl<S: TemperatureScale> Display for Temperature<S> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
-        Display::fmt(&self.value, f)?;
+        Display::fmt(&Value(self.value), f)?;
         write!(f, "°{}", S::LETTER)
     }
 }
@@ -335,15 +911,184 @@ pub struct SensorAcquisition<S: TemperatureScale> {
     pub relative_humidity: f32,
 }
 
+// A channel compiled out with the `temperature-only` or `humidity-only` feature holds NaN,
+// and is left out of comparisons and formatting.
+const HAS_TEMPERATURE: bool = !cfg!(feature = "humidity-only");
+const HAS_HUMIDITY: bool = !cfg!(feature = "temperature-only");
+
+impl<S: TemperatureScale> PartialEq for SensorAcquisition<S> {
+    fn eq(&self, other: &Self) -> bool {
+        (!HAS_TEMPERATURE || self.temperature == other.temperature)
+            && (!HAS_HUMIDITY || self.relative_humidity == other.relative_humidity)
+    }
+}
+
+impl<S: TemperatureScale> SensorAcquisition<S> {
+    /// Round the values to the meaningful resolution of the sensor, given by
+    /// [`TEMPERATURE_RESOLUTION`] and [`HUMIDITY_RESOLUTION`].
+    pub fn rounded(&self) -> Self {
+        Self {
+            temperature: self.temperature.round_to(TEMPERATURE_RESOLUTION),
+            relative_humidity: round_to(self.relative_humidity, HUMIDITY_RESOLUTION),
+        }
+    }
+
+    /// Write a compact representation such as `23.4C 45.1%` to `out`, for example a
+    /// `heapless::String`, without floating point formatting.
+    ///
+    /// A channel compiled out with the `temperature-only` or `humidity-only` feature is
+    /// omitted.
+    pub fn format_into<W: Write>(&self, out: &mut W) -> core::fmt::Result {
+        if HAS_TEMPERATURE {
+            write_fixed(out, self.temperature.value, 1)?;
+            out.write_char(S::LETTER)?;
+        }
+        if HAS_TEMPERATURE && HAS_HUMIDITY {
+            out.write_char(' ')?;
+        }
+        if HAS_HUMIDITY {
+            write_fixed(out, self.relative_humidity, 1)?;
+            out.write_char('%')?;
+        }
+        Ok(())
+    }
+
+    /// Compare with another acquisition, allowing the temperature to differ by up to
+    /// `temperature_epsilon` and the relative humidity by up to `humidity_epsilon`.
+    ///
+    /// A channel compiled out with the `temperature-only` or `humidity-only` feature is not
+    /// compared.
+    pub fn approx_eq(&self, other: &Self, temperature_epsilon: f32, humidity_epsilon: f32) -> bool {
+        (!HAS_TEMPERATURE
+            || within(
+                self.temperature.value,
+                other.temperature.value,
+                temperature_epsilon,
+            ))
+            && (!HAS_HUMIDITY
+                || within(
+                    self.relative_humidity,
+                    other.relative_humidity,
+                    humidity_epsilon,
+                ))
+    }
+}
+
+// Whether the status reports a new sample for the channels compiled in
+fn sample_available(status: &Status) -> bool {
+    if cfg!(feature = "temperature-only") {
+        status.temperature_available()
+    } else if cfg!(feature = "humidity-only") {
+        status.humidity_available()
+    } else {
+        status.temperature_available() && status.humidity_available()
+    }
+}
+
+fn within(a: f32, b: f32, epsilon: f32) -> bool {
+    a - b <= epsilon && b - a <= epsilon
+}
+
+// Write a value with a fixed number of decimals (at most 6), without floating point formatting
+fn write_fixed<W: Write>(out: &mut W, value: f32, decimals: u8) -> core::fmt::Result {
+    if value.is_nan() {
+        return out.write_str("NaN");
+    } else if value.is_infinite() {
+        return out.write_str(if value < 0.0 { "-inf" } else { "inf" });
+    }
+    let decimals = decimals.min(6);
+    let scale = 10u64.pow(decimals as u32);
+    let magnitude = if value < 0.0 { -value } else { value };
+    // Large values are whole numbers, which u128 holds up to f32::MAX. Otherwise the
+    // fraction is scaled on its own, so it keeps the full precision of the f32.
+    let (whole, fraction) = if magnitude >= WHOLE {
+        (magnitude as u128, 0)
+    } else {
+        let whole = magnitude as u64;
+        let fraction = round_to((magnitude - whole as f32) * scale as f32, 1.0) as u64;
+        if fraction == scale {
+            (whole as u128 + 1, 0)
+        } else {
+            (whole as u128, fraction)
+        }
+    };
+    let sign = if value < 0.0 && (whole, fraction) != (0, 0) {
+        "-"
+    } else {
+        ""
+    };
+    if decimals == 0 {
+        write!(out, "{}{}", sign, whole)
+    } else {
+        write!(
+            out,
+            "{}{}.{:0width$}",
+            sign,
+            whole,
+            fraction,
+            width = decimals as usize
+        )
+    }
+}
+
+// Magnitude from which every f32 is a whole number
+const WHOLE: f32 = 8_388_608.0;
+
+fn round_to(value: f32, step: f32) -> f32 {
+    let steps = value / step;
+    // Whole numbers of steps, infinities and NaN are left as they are
+    if !(-WHOLE < steps && steps < WHOLE) {
+        return value;
+    }
+    let steps = if steps < 0.0 {
+        (steps - 0.5) as i64
+    } else {
+        (steps + 0.5) as i64
+    };
+    steps as f32 * step
+}
+
 impl<S: TemperatureScale> Debug for SensorAcquisition<S> {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("SensorAcquisition")
             .field("temperature", &self.temperature)
-            .field("relative_humidity", &self.relative_humidity)
+            .field("relative_humidity", &Value(self.relative_humidity))
             .finish()
     }
 }
 
+// Formats a value with core::fmt when the `fmt` feature is enabled, and otherwise with two
+// decimals using integer arithmetic, keeping the f32 formatting code out of the binary.
+struct Value(f32);
+
+#[cfg(feature = "fmt")]
+impl Debug for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Debug::fmt(&self.0, f)
+    }
+}
+
+#[cfg(feature = "fmt")]
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(not(feature = "fmt"))]
+impl Debug for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write_fixed(f, self.0, 2)
+    }
+}
+
+#[cfg(not(feature = "fmt"))]
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write_fixed(f, self.0, 2)
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl<S: TemperatureScale> defmt::Format for SensorAcquisition<S> {
     fn format(&self, f: defmt::Formatter<'_>) {
@@ -355,3 +1100,264 @@ impl<S: TemperatureScale> defmt::Format for SensorAcquisition<S> {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    // Run the driver operations, ignoring their results: the tests only check for panics
+    fn exercise<I: Interface>(hts221: &mut Hts221<I>) {
+        block_on(async {
+            let _ = hts221.initialize().await;
+            if let Ok(acquisition) = hts221.read().await {
+                let mut out = std::string::String::new();
+                let _ = acquisition.format_into(&mut out);
+                let _ = std::format!("{:?} {}", acquisition, acquisition.temperature);
+            }
+            let _ = hts221.read_qualified().await;
+            let _ = hts221.health().await;
+            let _ = hts221.check_reset().await;
+            if let Ok(snapshot) = hts221.save_config().await {
+                let _ = hts221.restore_config(&snapshot).await;
+            }
+            let _ = hts221.heater_pulse(&mut NoDelay::default(), 10).await;
+            let mut samples = [SensorAcquisition {
+                temperature: 0.0.into(),
+                relative_humidity: 0.0,
+            }; 2];
+            let _ = hts221
+                .read_into(&mut samples, &mut NoDelay::default())
+                .await;
+        });
+    }
+
+    fn record() -> std::vec::Vec<u8> {
+        let mut buf = [0; 4096];
+        let mut hts221 = Hts221::new(Recorder::new(FakeSensor::new(), &mut buf));
+        block_on(hts221.initialize()).unwrap();
+        exercise(&mut hts221);
+        let recorder = hts221.release().release();
+        assert!(!recorder.overflowed());
+        recorder.release().1.to_vec()
+    }
+
+    #[test]
+    fn no_panic_on_truncated_trace() {
+        let trace = record();
+        for len in 0..=trace.len() {
+            exercise(&mut Hts221::new(Replayer::new(&trace[..len])));
+        }
+    }
+
+    #[test]
+    fn no_panic_on_corrupt_trace() {
+        let trace = record();
+        for i in 0..trace.len() {
+            for mask in [0x01, 0x80, 0xFF] {
+                let mut corrupt = trace.clone();
+                corrupt[i] ^= mask;
+                exercise(&mut Hts221::new(Replayer::new(&corrupt)));
+            }
+        }
+    }
+
+    #[test]
+    fn no_panic_on_bus_errors() {
+        for error in [ErrorKind::Bus, ErrorKind::ArbitrationLoss, ErrorKind::Other] {
+            for from in 0..200 {
+                let mut sensor = FakeSensor::new();
+                sensor.fail_from = Some(from);
+                sensor.error = error;
+                exercise(&mut Hts221::with_interface(sensor));
+            }
+            for register in [WHO_AM_I, AV_CONF, CTRL_REG1, CTRL_REG2, CTRL_REG3, STATUS] {
+                let mut sensor = FakeSensor::new();
+                sensor.fail_register = Some(register);
+                sensor.error = error;
+                exercise(&mut Hts221::with_interface(sensor));
+            }
+        }
+    }
+
+    #[test]
+    fn no_panic_on_extreme_values() {
+        for raw in [i16::MIN, -1, 0, 1, i16::MAX] {
+            let mut sensor = FakeSensor::new();
+            // Calibration points at the ends of the raw range
+            sensor.regs[0x36..0x38].copy_from_slice(&i16::MIN.to_le_bytes());
+            sensor.regs[0x3A..0x3C].copy_from_slice(&i16::MAX.to_le_bytes());
+            sensor.regs[0x3C..0x3E].copy_from_slice(&i16::MAX.to_le_bytes());
+            sensor.regs[0x3E..0x40].copy_from_slice(&i16::MIN.to_le_bytes());
+            sensor.regs[0x28..0x2A].copy_from_slice(&raw.to_le_bytes());
+            sensor.regs[0x2A..0x2C].copy_from_slice(&raw.to_le_bytes());
+            exercise(&mut Hts221::with_interface(sensor));
+        }
+        for block in [[0x00; 16], [0xFF; 16]] {
+            let mut sensor = FakeSensor::new();
+            sensor.regs[0x30..].copy_from_slice(&block);
+            exercise(&mut Hts221::with_interface(sensor));
+        }
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+    fn reads_calibrated_values() {
+        let mut hts221 = initialized();
+        sensor(&mut hts221).sample(22.5, 55.0);
+        let acquisition = block_on(hts221.read()).unwrap();
+        let expected = SensorAcquisition {
+            temperature: 22.5.into(),
+            relative_humidity: 55.0,
+        };
+        assert!(acquisition.approx_eq(&expected, 0.01, 0.01));
+    }
+
+    #[test]
+    fn heater_pulse_switches_heater_off() {
+        let mut hts221 = initialized();
+        let mut delay = NoDelay::default();
+        block_on(hts221.heater_pulse(&mut delay, 100)).unwrap();
+        assert!(!sensor(&mut hts221).heater_on());
+        assert_eq!(delay.total_us, 100_000);
+    }
+
+    #[test]
+    fn times_out_without_new_samples() {
+        let mut hts221 = initialized();
+        let config = Config {
+            output_data_rate: OutputDataRate::Hz1,
+            ..hts221.config()
+        };
+        block_on(hts221.reconfigure(config)).unwrap();
+        block_on(hts221.read()).unwrap();
+        sensor(&mut hts221).sample_every = None;
+
+        let mut delay = NoDelay::default();
+        let mut samples = [SensorAcquisition {
+            temperature: 0.0.into(),
+            relative_humidity: 0.0,
+        }; 2];
+        assert!(matches!(
+            block_on(hts221.read_into(&mut samples, &mut delay)),
+            Err(Hts221Error::Timeout)
+        ));
+        assert_eq!(delay.total_us, READY_PERIODS as u64 * 1_000_000);
+    }
+
+    #[test]
+    fn times_out_without_one_shot_completion() {
+        let mut hts221 = initialized();
+        let config = Config {
+            output_data_rate: OutputDataRate::OneShot,
+            ..hts221.config()
+        };
+        block_on(hts221.reconfigure(config)).unwrap();
+        sensor(&mut hts221).one_shot_polls = usize::MAX;
+
+        let mut samples = hts221.samples(NoDelay::default());
+        assert!(matches!(
+            block_on(samples.next_sample()),
+            Err(Hts221Error::Timeout)
+        ));
+    }
+
+    fn formatted(temperature: f32, relative_humidity: f32) -> std::string::String {
+        let mut out = std::string::String::new();
+        SensorAcquisition::<Celsius> {
+            temperature: temperature.into(),
+            relative_humidity,
+        }
+        .format_into(&mut out)
+        .unwrap();
+        out
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+    fn formats_both_channels() {
+        assert_eq!(formatted(23.44, 45.06), "23.4C 45.1%");
+        assert_eq!(formatted(f32::NAN, f32::NEG_INFINITY), "NaNC -inf%");
+    }
+
+    #[test]
+    #[cfg(feature = "temperature-only")]
+    fn leaves_out_humidity() {
+        assert_eq!(formatted(23.44, f32::NAN), "23.4C");
+        let a = SensorAcquisition::<Celsius> {
+            temperature: 23.0.into(),
+            relative_humidity: f32::NAN,
+        };
+        assert!(a == a);
+        assert!(a.approx_eq(&a, 0.0, 0.0));
+    }
+
+    #[test]
+    #[cfg(feature = "humidity-only")]
+    fn leaves_out_temperature() {
+        assert_eq!(formatted(f32::NAN, 45.06), "45.1%");
+        let a = SensorAcquisition::<Celsius> {
+            temperature: f32::NAN.into(),
+            relative_humidity: 45.0,
+        };
+        assert!(a == a);
+        assert!(a.approx_eq(&a, 0.0, 0.0));
+    }
+
+    #[test]
+    fn formats_large_values_exactly() {
+        let fixed = |value: f32, decimals: u8| {
+            let mut out = std::string::String::new();
+            write_fixed(&mut out, value, decimals).unwrap();
+            out
+        };
+        assert_eq!(fixed(2148.5, 6), "2148.500000");
+        assert_eq!(fixed(-2148.5, 6), "-2148.500000");
+        assert_eq!(fixed(8_388_607.5, 1), "8388607.5");
+        assert_eq!(fixed(16_777_216.0, 2), "16777216.00");
+        assert_eq!(fixed(1e20, 0), "100000002004087734272");
+        assert_eq!(
+            fixed(f32::MAX, 1),
+            "340282346638528859811704183484516925440.0"
+        );
+        assert_eq!(
+            fixed(f32::MIN, 0),
+            "-340282346638528859811704183484516925440"
+        );
+        assert_eq!(fixed(-0.001, 2), "0.00");
+        assert_eq!(fixed(f32::NAN, 2), "NaN");
+        assert_eq!(fixed(f32::INFINITY, 2), "inf");
+        assert_eq!(fixed(f32::NEG_INFINITY, 2), "-inf");
+    }
+
+    #[test]
+    fn rounds_large_values_exactly() {
+        assert_eq!(round_to(3000.26, 0.1), 3000.3);
+        assert_eq!(round_to(-3000.26, 0.1), -3000.3);
+        assert_eq!(round_to(1e10, 0.1), 1e10);
+        assert_eq!(round_to(f32::MAX, 0.5), f32::MAX);
+        assert_eq!(round_to(f32::INFINITY, 0.1), f32::INFINITY);
+        assert_eq!(round_to(f32::NEG_INFINITY, 0.1), f32::NEG_INFINITY);
+        assert!(round_to(f32::NAN, 0.1).is_nan());
+    }
+
+    #[test]
+    fn formats_non_finite_values() {
+        for value in [
+            f32::NAN,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::MAX,
+            f32::MIN,
+        ] {
+            let acquisition = SensorAcquisition {
+                temperature: value.into(),
+                relative_humidity: value,
+            };
+            let mut out = std::string::String::new();
+            acquisition.format_into(&mut out).unwrap();
+            let _ = acquisition.rounded();
+            let _ = acquisition.approx_eq(&acquisition, value, value);
+        }
+    }
+}
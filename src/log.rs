@@ -0,0 +1,216 @@
+use super::{Celsius, SensorAcquisition, Temperature};
+use fugit::{TimerDurationU32, TimerInstantU32};
+
+/// An acquisition recorded by a [`SampleLog`].
+#[derive(Copy, Clone)]
+pub struct LogEntry<const HZ: u32> {
+    /// Sensor values.
+    pub acquisition: SensorAcquisition<Celsius>,
+    /// Time elapsed since the previous entry, or zero for the first entry logged.
+    pub interval: TimerDurationU32<HZ>,
+}
+
+/// Ring buffer keeping the last `N` acquisitions, with the interval between them measured by
+/// a tick source running at `HZ`.
+///
+/// The intervals allow exported history to be placed on a timeline even without wall-clock
+/// time. Once full, the oldest entry is overwritten.
+pub struct SampleLog<const N: usize, const HZ: u32> {
+    entries: [Option<LogEntry<HZ>>; N],
+    next: usize,
+    len: usize,
+    last: Option<TimerInstantU32<HZ>>,
+}
+
+impl<const N: usize, const HZ: u32> SampleLog<N, HZ> {
+    /// Create an empty log.
+    pub fn new() -> Self {
+        Self {
+            entries: [None; N],
+            next: 0,
+            len: 0,
+            last: None,
+        }
+    }
+
+    /// Record an acquisition taken at `now`.
+    pub fn push(&mut self, acquisition: SensorAcquisition<Celsius>, now: TimerInstantU32<HZ>) {
+        if N == 0 {
+            return;
+        }
+        let interval = match self.last {
+            Some(last) => now
+                .checked_duration_since(last)
+                .unwrap_or_else(|| TimerDurationU32::from_ticks(0)),
+            None => TimerDurationU32::from_ticks(0),
+        };
+        self.last.replace(now);
+        self.entries[self.next] = Some(LogEntry {
+            acquisition,
+            interval,
+        });
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    /// Number of entries in the log.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the log has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over the entries, from the oldest to the most recent.
+    pub fn iter(&self) -> impl Iterator<Item = &LogEntry<HZ>> + '_ {
+        let start = (self.next + N - self.len) % N.max(1);
+        (0..self.len).filter_map(move |i| self.entries[(start + i) % N].as_ref())
+    }
+
+    /// The most recent entry.
+    pub fn latest(&self) -> Option<&LogEntry<HZ>> {
+        if self.len == 0 {
+            None
+        } else {
+            self.entries[(self.next + N - 1) % N].as_ref()
+        }
+    }
+
+    /// Temperature below which `percentile` percent of the last `count` entries fall, for
+    /// example the median with 50.
+    ///
+    /// Uses the nearest-rank method, so the result is one of the logged values. Unlike a
+    /// mean, it is not skewed by a few spikes. `None` if there are no entries.
+    pub fn temperature_percentile(
+        &self,
+        count: usize,
+        percentile: f32,
+    ) -> Option<Temperature<Celsius>> {
+        self.percentile(count, percentile, |acquisition| {
+            acquisition.temperature.raw_value()
+        })
+        .map(Into::into)
+    }
+
+    /// Relative humidity below which `percentile` percent of the last `count` entries fall,
+    /// see [`temperature_percentile`](Self::temperature_percentile).
+    pub fn humidity_percentile(&self, count: usize, percentile: f32) -> Option<f32> {
+        self.percentile(count, percentile, |acquisition| {
+            acquisition.relative_humidity
+        })
+    }
+
+    // Nearest-rank percentile, selected in a copy of the values on the stack. NaN values,
+    // as reported for a compiled out channel, are skipped.
+    fn percentile<F>(&self, count: usize, percentile: f32, value: F) -> Option<f32>
+    where
+        F: Fn(&SensorAcquisition<Celsius>) -> f32,
+    {
+        if percentile.is_nan() {
+            return None;
+        }
+        let mut values = [0.0; N];
+        let mut n = 0;
+        for entry in self.iter().skip(self.len - count.min(self.len)) {
+            let v = value(&entry.acquisition);
+            if let (false, Some(slot)) = (v.is_nan(), values.get_mut(n)) {
+                *slot = v;
+                n += 1;
+            }
+        }
+        if n == 0 {
+            return None;
+        }
+
+        let rank = percentile.clamp(0.0, 100.0) / 100.0 * n as f32;
+        let mut index = rank as usize;
+        if (index as f32) < rank {
+            index += 1;
+        }
+        let index = index.clamp(1, n) - 1;
+        let (_, v, _) = values[..n].select_nth_unstable_by(index, |a, b| a.total_cmp(b));
+        Some(*v)
+    }
+
+    /// Remove all entries.
+    pub fn clear(&mut self) {
+        self.entries = [None; N];
+        self.next = 0;
+        self.len = 0;
+        self.last = None;
+    }
+}
+
+impl<const N: usize, const HZ: u32> Default for SampleLog<N, HZ> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(values: &[f32]) -> SampleLog<4, 1000> {
+        let mut log = SampleLog::new();
+        for (i, &v) in values.iter().enumerate() {
+            let acquisition = SensorAcquisition {
+                temperature: v.into(),
+                relative_humidity: v * 2.0,
+            };
+            log.push(acquisition, TimerInstantU32::from_ticks(i as u32 * 100));
+        }
+        log
+    }
+
+    #[test]
+    fn median_ignores_spike() {
+        let log = log(&[21.0, 80.0, 20.0, 22.0]);
+        assert_eq!(
+            log.temperature_percentile(4, 50.0).unwrap().raw_value(),
+            21.0
+        );
+        assert_eq!(log.humidity_percentile(4, 50.0), Some(42.0));
+        assert_eq!(
+            log.temperature_percentile(4, 100.0).unwrap().raw_value(),
+            80.0
+        );
+        assert_eq!(
+            log.temperature_percentile(4, 0.0).unwrap().raw_value(),
+            20.0
+        );
+    }
+
+    #[test]
+    fn uses_most_recent_entries() {
+        // The first two values are overwritten, the window covers the last two
+        let log = log(&[1.0, 2.0, 30.0, 10.0, 20.0, 40.0]);
+        assert_eq!(
+            log.temperature_percentile(2, 50.0).unwrap().raw_value(),
+            20.0
+        );
+        assert_eq!(
+            log.temperature_percentile(10, 0.0).unwrap().raw_value(),
+            10.0
+        );
+    }
+
+    #[test]
+    fn skips_missing_values() {
+        let log = log(&[f32::NAN, 5.0]);
+        assert_eq!(log.temperature_percentile(2, 0.0).unwrap().raw_value(), 5.0);
+        assert!(log.temperature_percentile(1, f32::NAN).is_none());
+        assert!(SampleLog::<4, 1000>::new()
+            .humidity_percentile(4, 50.0)
+            .is_none());
+    }
+
+    #[test]
+    fn measures_intervals() {
+        let log = log(&[1.0, 2.0]);
+        let intervals: std::vec::Vec<u32> = log.iter().map(|e| e.interval.ticks()).collect();
+        assert_eq!(intervals, [0, 100]);
+    }
+}
@@ -0,0 +1,84 @@
+//! Floating point functions used by derived quantities, provided by the selected backend.
+
+#[cfg(all(feature = "libm", feature = "micromath"))]
+compile_error!("features `libm` and `micromath` are mutually exclusive");
+
+#[cfg(feature = "libm")]
+pub(crate) fn ln(x: f32) -> f32 {
+    libm::logf(x)
+}
+
+#[cfg(feature = "libm")]
+pub(crate) fn exp(x: f32) -> f32 {
+    libm::expf(x)
+}
+
+// The micromath logarithm is off by up to 15 % between 0.5 and 1, the range of relative
+// humidity ratios, so it is refined with Newton steps on exp(y) = x.
+#[cfg(feature = "micromath")]
+pub(crate) fn ln(x: f32) -> f32 {
+    if x.is_nan() || x < 0.0 {
+        return f32::NAN;
+    }
+    if x == 0.0 {
+        return f32::NEG_INFINITY;
+    }
+    if x == f32::INFINITY {
+        return x;
+    }
+    let mut y = micromath::F32(x).ln().0;
+    for _ in 0..2 {
+        y += x / exp(y) - 1.0;
+    }
+    y
+}
+
+#[cfg(feature = "micromath")]
+pub(crate) fn exp(x: f32) -> f32 {
+    if x.is_nan() {
+        return x;
+    }
+    micromath::F32(x).exp().0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ln_is_accurate() {
+        for x in [0.01, 0.1, 0.5, 0.6, 0.9, 0.99, 1.0, 2.0, 10.0, 1000.0] {
+            let expected = std::primitive::f32::ln(x);
+            let tolerance = 1e-3 * expected.abs().max(1.0);
+            assert!(
+                (ln(x) - expected).abs() < tolerance,
+                "ln({}) = {}",
+                x,
+                ln(x)
+            );
+        }
+    }
+
+    #[test]
+    fn exp_is_accurate() {
+        for x in [-10.0, -1.0, 0.0, 0.5, 1.0, 2.5, 10.0] {
+            let expected = std::primitive::f32::exp(x);
+            assert!(
+                (exp(x) / expected - 1.0).abs() < 1e-3,
+                "exp({}) = {}",
+                x,
+                exp(x)
+            );
+        }
+    }
+
+    #[test]
+    fn special_values() {
+        assert_eq!(ln(0.0), f32::NEG_INFINITY);
+        assert_eq!(ln(f32::INFINITY), f32::INFINITY);
+        assert!(ln(-1.0).is_nan());
+        assert!(ln(f32::NAN).is_nan());
+        assert!(exp(f32::NAN).is_nan());
+        assert_eq!(exp(f32::NEG_INFINITY), 0.0);
+    }
+}
@@ -0,0 +1,256 @@
+#[cfg(not(feature = "humidity-only"))]
+use super::Temperature;
+use super::{Celsius, Config, Hts221Error, SensorAcquisition};
+use core::convert::Infallible;
+use embedded_hal_async::delay::DelayUs;
+
+// Failures which can be scripted ahead with `MockHts221::fail_next`
+const FAILURES: usize = 4;
+
+/// A mock of the HTS221 driver exposing the same API as [`Hts221`](super::Hts221).
+///
+/// Values are taken from the given temperature and humidity sequences, which wrap around
+/// once exhausted. Reading from an empty sequence fails with [`Hts221Error::Timeout`], like
+/// a sensor which never produces a sample. Useful for unit testing application logic
+/// without real hardware.
+pub struct MockHts221<'a> {
+    temperatures: &'a [f32],
+    humidities: &'a [f32],
+    next_temperature: usize,
+    next_humidity: usize,
+    initialized: bool,
+    config: Config,
+    failures: [Option<Hts221Error<Infallible>>; FAILURES],
+}
+
+impl<'a> MockHts221<'a> {
+    /// Create a new mock fed from the given temperature (in celsius) and relative humidity sequences.
+    pub fn new(temperatures: &'a [f32], humidities: &'a [f32]) -> Self {
+        Self {
+            temperatures,
+            humidities,
+            next_temperature: 0,
+            next_humidity: 0,
+            initialized: false,
+            config: Config::default(),
+            failures: Default::default(),
+        }
+    }
+
+    /// Make the next call to the mock fail with `error`.
+    ///
+    /// Failures are queued, each failing one call in the order they were added. Up to four
+    /// failures can be queued; beyond that the error is handed back.
+    pub fn fail_next(
+        &mut self,
+        error: Hts221Error<Infallible>,
+    ) -> Result<(), Hts221Error<Infallible>> {
+        match self.failures.iter_mut().find(|failure| failure.is_none()) {
+            Some(free) => {
+                free.replace(error);
+                Ok(())
+            }
+            None => Err(error),
+        }
+    }
+
+    /// Initialize the mock. Must be run before reading sensor values.
+    pub async fn initialize(&mut self) -> Result<(), Hts221Error<Infallible>> {
+        self.scripted()?;
+        self.initialized = true;
+        Ok(())
+    }
+
+    /// Read the next sensor values from the sequences.
+    ///
+    /// A channel compiled out is reported as NaN, as by the driver.
+    pub async fn read(&mut self) -> Result<SensorAcquisition<Celsius>, Hts221Error<Infallible>> {
+        self.scripted()?;
+        self.acquire()
+    }
+
+    /// Read the next temperature value from the sequence.
+    #[cfg(not(feature = "humidity-only"))]
+    pub async fn read_temperature(
+        &mut self,
+    ) -> Result<Temperature<Celsius>, Hts221Error<Infallible>> {
+        self.scripted()?;
+        self.next_temperature().map(Into::into)
+    }
+
+    /// Read the next relative humidity value from the sequence.
+    #[cfg(not(feature = "temperature-only"))]
+    pub async fn read_humidity(&mut self) -> Result<f32, Hts221Error<Infallible>> {
+        self.scripted()?;
+        self.next_humidity()
+    }
+
+    /// Fill `samples` with the next sensor values from the sequences. The delay is not used,
+    /// it only keeps the signature of the driver.
+    pub async fn read_into<D: DelayUs>(
+        &mut self,
+        samples: &mut [SensorAcquisition<Celsius>],
+        _delay: &mut D,
+    ) -> Result<(), Hts221Error<Infallible>> {
+        self.scripted()?;
+        for sample in samples.iter_mut() {
+            *sample = self.acquire()?;
+        }
+        Ok(())
+    }
+
+    /// The configuration applied by the mock.
+    pub fn config(&self) -> Config {
+        self.config
+    }
+
+    /// Switch to a new configuration, which is only stored.
+    pub async fn reconfigure(&mut self, config: Config) -> Result<(), Hts221Error<Infallible>> {
+        self.scripted()?;
+        self.config = config;
+        Ok(())
+    }
+
+    // Fail with the oldest queued failure
+    fn scripted(&mut self) -> Result<(), Hts221Error<Infallible>> {
+        let failure = self.failures[0].take();
+        self.failures.rotate_left(1);
+        failure.map_or(Ok(()), Err)
+    }
+
+    fn acquire(&mut self) -> Result<SensorAcquisition<Celsius>, Hts221Error<Infallible>> {
+        #[cfg(not(feature = "humidity-only"))]
+        let temperature = self.next_temperature()?.into();
+        #[cfg(feature = "humidity-only")]
+        let temperature = self.next_temperature().map(|_| f32::NAN.into())?;
+        #[cfg(not(feature = "temperature-only"))]
+        let relative_humidity = self.next_humidity()?;
+        #[cfg(feature = "temperature-only")]
+        let relative_humidity = self.next_humidity().map(|_| f32::NAN)?;
+
+        Ok(SensorAcquisition {
+            temperature,
+            relative_humidity,
+        })
+    }
+
+    fn next_temperature(&mut self) -> Result<f32, Hts221Error<Infallible>> {
+        if !self.initialized {
+            return Err(Hts221Error::NotCalibrated);
+        }
+        next(self.temperatures, &mut self.next_temperature)
+    }
+
+    fn next_humidity(&mut self) -> Result<f32, Hts221Error<Infallible>> {
+        if !self.initialized {
+            return Err(Hts221Error::NotCalibrated);
+        }
+        next(self.humidities, &mut self.next_humidity)
+    }
+}
+
+fn next(values: &[f32], index: &mut usize) -> Result<f32, Hts221Error<Infallible>> {
+    if values.is_empty() {
+        return Err(Hts221Error::Timeout);
+    }
+    let value = values[*index % values.len()];
+    *index = (*index + 1) % values.len();
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::OutputDataRate;
+
+    fn initialized_mock<'a>(temperatures: &'a [f32], humidities: &'a [f32]) -> MockHts221<'a> {
+        let mut mock = MockHts221::new(temperatures, humidities);
+        block_on(mock.initialize()).unwrap();
+        mock
+    }
+
+    #[test]
+    fn requires_initialization() {
+        let mut mock = MockHts221::new(&[20.0], &[50.0]);
+        assert!(matches!(
+            block_on(mock.read()),
+            Err(Hts221Error::NotCalibrated)
+        ));
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+    fn wraps_around_sequences() {
+        let mut mock = initialized_mock(&[20.0, 21.0], &[40.0, 41.0, 42.0]);
+        let temperatures: std::vec::Vec<f32> = (0..3)
+            .map(|_| block_on(mock.read_temperature()).unwrap().value)
+            .collect();
+        assert_eq!(temperatures, [20.0, 21.0, 20.0]);
+        let humidities: std::vec::Vec<f32> = (0..4)
+            .map(|_| block_on(mock.read_humidity()).unwrap())
+            .collect();
+        assert_eq!(humidities, [40.0, 41.0, 42.0, 40.0]);
+    }
+
+    #[test]
+    fn reads_both_sequences() {
+        let mut mock = initialized_mock(&[20.0, 21.0], &[40.0, 41.0]);
+        let mut samples = [SensorAcquisition {
+            temperature: 0.0.into(),
+            relative_humidity: 0.0,
+        }; 2];
+        block_on(mock.read_into(&mut samples, &mut NoDelay::default())).unwrap();
+        let expected = SensorAcquisition {
+            temperature: 21.0.into(),
+            relative_humidity: 41.0,
+        };
+        assert!(samples[1].approx_eq(&expected, 0.0, 0.0));
+    }
+
+    #[test]
+    fn fails_on_empty_sequence() {
+        let mut mock = initialized_mock(&[], &[]);
+        assert!(matches!(block_on(mock.read()), Err(Hts221Error::Timeout)));
+    }
+
+    #[test]
+    fn fails_scripted_calls_in_order() {
+        let mut mock = initialized_mock(&[20.0], &[50.0]);
+        mock.fail_next(Hts221Error::Timeout).unwrap();
+        mock.fail_next(Hts221Error::BadCalibration).unwrap();
+        assert!(matches!(block_on(mock.read()), Err(Hts221Error::Timeout)));
+        assert!(matches!(
+            block_on(mock.reconfigure(Config::default())),
+            Err(Hts221Error::BadCalibration)
+        ));
+        assert!(block_on(mock.read()).is_ok());
+    }
+
+    #[test]
+    fn hands_back_failures_beyond_capacity() {
+        let mut mock = initialized_mock(&[20.0], &[50.0]);
+        for _ in 0..FAILURES {
+            mock.fail_next(Hts221Error::Timeout).unwrap();
+        }
+        assert!(matches!(
+            mock.fail_next(Hts221Error::HeaterActive),
+            Err(Hts221Error::HeaterActive)
+        ));
+        for _ in 0..FAILURES {
+            assert!(block_on(mock.read()).is_err());
+        }
+        assert!(block_on(mock.read()).is_ok());
+    }
+
+    #[test]
+    fn stores_configuration() {
+        let mut mock = initialized_mock(&[20.0], &[50.0]);
+        let config = Config {
+            output_data_rate: OutputDataRate::Hz12p5,
+            ..mock.config()
+        };
+        block_on(mock.reconfigure(config)).unwrap();
+        assert_eq!(mock.config().output_data_rate, OutputDataRate::Hz12p5);
+    }
+}
@@ -0,0 +1,163 @@
+#[cfg(not(feature = "temperature-only"))]
+use super::register::h_out::Hout;
+#[cfg(not(feature = "humidity-only"))]
+use super::register::t_out::Tout;
+use super::{
+    register::{
+        calibration::Calibration,
+        ctrl1::{BlockDataUpdate, Ctrl1, OutputDataRate},
+        ctrl2::Ctrl2,
+        who_am_i::{WhoAmI, HTS221_ID},
+    },
+    Celsius, Hts221Error, I2cInterface, SensorAcquisition, ONE_SHOT_ATTEMPTS, ONE_SHOT_POLL_MS,
+};
+use embedded_hal_async::{
+    delay::DelayUs,
+    i2c::{I2c, SevenBitAddress},
+};
+
+/// Take a single measurement without keeping a driver instance.
+///
+/// The sensor is probed, a one-shot acquisition is started and polled for completion using
+/// `delay`, the calibration is read to convert the values, and the sensor is powered down
+/// again. Useful for bootloaders and quick diagnostics.
+///
+/// Fails with [`Hts221Error::Timeout`] if the acquisition does not complete. Once powered
+/// on, the sensor is powered down again whatever the outcome of the acquisition.
+pub async fn measure_once<I: I2c<SevenBitAddress>, D: DelayUs>(
+    i2c: &mut I,
+    delay: &mut D,
+) -> Result<SensorAcquisition<Celsius>, Hts221Error<I::Error>> {
+    let mut interface = I2cInterface::new(i2c);
+
+    let who_am_i = WhoAmI::read(&mut interface).await?;
+    if who_am_i != HTS221_ID {
+        return Err(Hts221Error::InvalidSensor {
+            found: who_am_i,
+            expected: HTS221_ID,
+        });
+    }
+    let calibration = Calibration::read(&mut interface).await?;
+    if !calibration.is_valid() {
+        return Err(Hts221Error::BadCalibration);
+    }
+
+    Ctrl1::modify(&mut interface, |reg| {
+        reg.power_active()
+            .output_data_rate(OutputDataRate::OneShot)
+            .block_data_update(BlockDataUpdate::MsbLsbReading);
+    })
+    .await?;
+    let result = acquire(&mut interface, &calibration, delay).await;
+    // Power down even after a failed acquisition, its error taking precedence
+    let powered_down = Ctrl1::modify(&mut interface, |reg| {
+        reg.power_down();
+    })
+    .await;
+    let acquisition = result?;
+    powered_down?;
+    Ok(acquisition)
+}
+
+async fn acquire<I: I2c<SevenBitAddress>, D: DelayUs>(
+    interface: &mut I2cInterface<I>,
+    calibration: &Calibration,
+    delay: &mut D,
+) -> Result<SensorAcquisition<Celsius>, Hts221Error<I::Error>> {
+    // Discard values left from an earlier acquisition, so the status is not satisfied by them
+    #[cfg(not(feature = "humidity-only"))]
+    Tout::read(interface).await?;
+    #[cfg(not(feature = "temperature-only"))]
+    Hout::read(interface).await?;
+    Ctrl2::modify(interface, |reg| {
+        reg.enable_one_shot();
+    })
+    .await?;
+    let mut attempts = 0;
+    while Ctrl2::read(interface).await?.one_shot_pending() {
+        attempts += 1;
+        if attempts == ONE_SHOT_ATTEMPTS {
+            return Err(Hts221Error::Timeout);
+        }
+        delay.delay_ms(ONE_SHOT_POLL_MS).await;
+    }
+
+    #[cfg(not(feature = "humidity-only"))]
+    let temperature = calibration.calibrated_temperature(Tout::read(interface).await?);
+    #[cfg(feature = "humidity-only")]
+    let temperature = f32::NAN.into();
+    #[cfg(not(feature = "temperature-only"))]
+    let relative_humidity = calibration.calibrated_humidity(Hout::read(interface).await?);
+    #[cfg(feature = "temperature-only")]
+    let relative_humidity = f32::NAN;
+
+    Ok(SensorAcquisition {
+        temperature,
+        relative_humidity,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn measures_once() {
+        let mut sensor = FakeSensor::new();
+        sensor.one_shot_polls = 3;
+        sensor.next_sample = Some((22.0, 60.0));
+        // Values left from an earlier acquisition
+        sensor.sample(30.0, 45.0);
+        let mut delay = NoDelay::default();
+        let acquisition = block_on(measure_once(&mut sensor, &mut delay)).unwrap();
+        #[cfg(not(feature = "humidity-only"))]
+        assert_eq!(acquisition.temperature.raw_value(), 22.0);
+        #[cfg(not(feature = "temperature-only"))]
+        assert_eq!(acquisition.relative_humidity, 60.0);
+        assert_eq!(delay.total_us, 2 * ONE_SHOT_POLL_MS as u64 * 1000);
+        assert_eq!(sensor.regs[CTRL_REG1 as usize] & 0x80, 0);
+    }
+
+    #[test]
+    fn times_out() {
+        let mut sensor = FakeSensor::new();
+        sensor.one_shot_polls = usize::MAX;
+        let mut delay = NoDelay::default();
+        let result = block_on(measure_once(&mut sensor, &mut delay));
+        assert!(matches!(result, Err(Hts221Error::Timeout)));
+        assert_eq!(sensor.regs[CTRL_REG1 as usize] & 0x80, 0);
+    }
+
+    #[test]
+    fn powers_down_after_failed_acquisition() {
+        #[cfg(not(feature = "humidity-only"))]
+        let output = T_OUT;
+        #[cfg(feature = "humidity-only")]
+        let output = H_OUT;
+        for register in [CTRL_REG2, output] {
+            let mut sensor = FakeSensor::new();
+            sensor.fail_register = Some(register);
+            let result = block_on(measure_once(&mut sensor, &mut NoDelay::default()));
+            assert!(matches!(result, Err(Hts221Error::I2c(_))));
+            assert_eq!(sensor.regs[CTRL_REG1 as usize] & 0x80, 0);
+            assert_eq!(
+                sensor.writes.last().map(|(register, _)| *register),
+                Some(CTRL_REG1)
+            );
+        }
+    }
+
+    #[test]
+    fn reports_failed_power_down() {
+        let mut sensor = FakeSensor::new();
+        let mut delay = NoDelay::default();
+        block_on(measure_once(&mut sensor, &mut delay)).unwrap();
+        // Fail only the last access, which writes the power down
+        let accesses = sensor.reads + sensor.writes.len();
+        let mut sensor = FakeSensor::new();
+        sensor.fail_from = Some(accesses - 1);
+        let result = block_on(measure_once(&mut sensor, &mut delay));
+        assert!(matches!(result, Err(Hts221Error::I2c(_))));
+    }
+}
@@ -0,0 +1,186 @@
+use super::{shared::WakerQueue, Celsius, Hts221, Hts221Error, Interface, SensorAcquisition};
+use core::{
+    cell::{Cell, UnsafeCell},
+    future::poll_fn,
+    ops::Deref,
+    task::Poll,
+};
+use embedded_hal_async::delay::DelayUs;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum BufferState {
+    Empty,
+    Filling,
+    Full,
+    Reading,
+}
+
+/// Double-buffered continuous capture into two caller-provided buffers.
+///
+/// [`capture`](Self::capture) fills the buffers alternately, while [`next`](Self::next)
+/// hands completed buffers to the application. Running both from independent tasks on the
+/// same executor lets one buffer be processed while the other fills, so processing time
+/// does not delay the acquisitions.
+pub struct PingPong<'b> {
+    buffers: [UnsafeCell<&'b mut [SensorAcquisition<Celsius>]>; 2],
+    states: [Cell<BufferState>; 2],
+    next_read: Cell<usize>,
+    waiter: WakerQueue,
+}
+
+impl<'b> PingPong<'b> {
+    /// Create a capture alternating between the two buffers.
+    pub fn new(
+        first: &'b mut [SensorAcquisition<Celsius>],
+        second: &'b mut [SensorAcquisition<Celsius>],
+    ) -> Self {
+        Self {
+            buffers: [UnsafeCell::new(first), UnsafeCell::new(second)],
+            states: [Cell::new(BufferState::Empty), Cell::new(BufferState::Empty)],
+            next_read: Cell::new(0),
+            waiter: WakerQueue::new(),
+        }
+    }
+
+    /// Fill the buffers alternately with consecutive acquisitions until an error occurs.
+    ///
+    /// Waits for a buffer to be released by the application before filling it again. A buffer
+    /// being filled when the capture fails or is dropped is left empty.
+    pub async fn capture<I: Interface, D: DelayUs>(
+        &self,
+        hts221: &mut Hts221<I>,
+        delay: &mut D,
+    ) -> Hts221Error<I::Error> {
+        let mut index = 0;
+        loop {
+            self.wait_for(index, BufferState::Empty, BufferState::Filling)
+                .await;
+            let mut filling = Filling {
+                ping_pong: self,
+                index,
+                done: BufferState::Empty,
+            };
+            // Safety: the buffer is in the filling state, so no guard refers to it.
+            let buffer = unsafe { &mut **self.buffers[index].get() };
+            if let Err(e) = hts221.read_into(buffer, delay).await {
+                return e;
+            }
+            filling.done = BufferState::Full;
+            drop(filling);
+            index = 1 - index;
+        }
+    }
+
+    /// Wait for the next completed buffer. It is filled again once the returned guard is dropped.
+    pub async fn next(&self) -> FilledBuffer<'_, 'b> {
+        let index = self.next_read.get();
+        self.wait_for(index, BufferState::Full, BufferState::Reading)
+            .await;
+        self.next_read.set(1 - index);
+        FilledBuffer {
+            ping_pong: self,
+            index,
+        }
+    }
+
+    async fn wait_for(&self, index: usize, state: BufferState, next: BufferState) {
+        poll_fn(|cx| {
+            if self.states[index].get() == state {
+                self.states[index].set(next);
+                Poll::Ready(())
+            } else {
+                self.waiter.register(cx.waker());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    fn set_state(&self, index: usize, state: BufferState) {
+        self.states[index].set(state);
+        self.waiter.wake();
+    }
+}
+
+// A buffer being filled, set to the `done` state when dropped
+struct Filling<'a, 'b> {
+    ping_pong: &'a PingPong<'b>,
+    index: usize,
+    done: BufferState,
+}
+
+impl<'a, 'b> Drop for Filling<'a, 'b> {
+    fn drop(&mut self) {
+        self.ping_pong.set_state(self.index, self.done);
+    }
+}
+
+/// A completed buffer of a [`PingPong`] capture.
+pub struct FilledBuffer<'a, 'b> {
+    ping_pong: &'a PingPong<'b>,
+    index: usize,
+}
+
+impl<'a, 'b> Deref for FilledBuffer<'a, 'b> {
+    type Target = [SensorAcquisition<Celsius>];
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: the buffer is in the reading state, so the capture does not touch it.
+        unsafe { &**self.ping_pong.buffers[self.index].get() }
+    }
+}
+
+impl<'a, 'b> Drop for FilledBuffer<'a, 'b> {
+    fn drop(&mut self) {
+        self.ping_pong.set_state(self.index, BufferState::Empty);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use core::{future::Future, task::Context};
+
+    fn acquisitions() -> [SensorAcquisition<Celsius>; 2] {
+        [SensorAcquisition {
+            temperature: 0.0.into(),
+            relative_humidity: 0.0,
+        }; 2]
+    }
+
+    #[test]
+    fn capture_waits_for_release() {
+        let (mut first, mut second) = (acquisitions(), acquisitions());
+        let ping_pong = PingPong::new(&mut first, &mut second);
+        let mut hts221 = initialized();
+        let mut delay = NoDelay::default();
+        let (waker, wakes) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+
+        let mut capture = Box::pin(ping_pong.capture(&mut hts221, &mut delay));
+        assert!(capture.as_mut().poll(&mut cx).is_pending());
+        assert!(capture.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(wakes(), 0);
+
+        let buffer = block_on(ping_pong.next());
+        assert_eq!(buffer.len(), 2);
+        drop(buffer);
+        assert_eq!(wakes(), 1);
+    }
+
+    #[test]
+    fn dropped_capture_empties_buffer() {
+        let (mut first, mut second) = (acquisitions(), acquisitions());
+        let ping_pong = PingPong::new(&mut first, &mut second);
+        let mut hts221 = initialized();
+        sensor(&mut hts221).sample_every = None;
+        let mut stall = Stall;
+
+        let mut capture = Box::pin(ping_pong.capture(&mut hts221, &mut stall));
+        assert!(poll_once(&mut capture).is_pending());
+        assert!(ping_pong.states[0].get() == BufferState::Filling);
+        drop(capture);
+        assert!(ping_pong.states[0].get() == BufferState::Empty);
+    }
+}
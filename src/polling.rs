@@ -0,0 +1,76 @@
+use super::{
+    executor::block_on, sample_available, Celsius, Hts221, Hts221Error, Interface,
+    SensorAcquisition, Status,
+};
+
+impl<I> Hts221<I>
+where
+    I: Interface,
+{
+    /// Read sensor values without an async executor.
+    ///
+    /// Returns [`nb::Error::WouldBlock`] until a new sample is available. The interface
+    /// operations are polled to completion in place, so this is intended for interfaces
+    /// backed by blocking transports.
+    pub fn poll_read(&mut self) -> nb::Result<SensorAcquisition<Celsius>, Hts221Error<I::Error>> {
+        let status: Status = block_on(self.status())?;
+        if sample_available(&status) {
+            Ok(block_on(self.read())?)
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::{Config, OutputDataRate};
+
+    fn continuous() -> Hts221<FakeSensor> {
+        let mut hts221 = initialized();
+        let config = Config {
+            output_data_rate: OutputDataRate::Hz1,
+            ..hts221.config()
+        };
+        block_on(hts221.reconfigure(config)).unwrap();
+        hts221
+    }
+
+    #[test]
+    fn would_block_until_a_sample_is_available() {
+        let mut hts221 = continuous();
+        sensor(&mut hts221).sample_every = None;
+        for _ in 0..3 {
+            assert!(matches!(hts221.poll_read(), Err(nb::Error::WouldBlock)));
+        }
+        sensor(&mut hts221).sample(22.0, 45.0);
+        let acquisition = hts221.poll_read().unwrap();
+        let expected = SensorAcquisition {
+            temperature: 22.0.into(),
+            relative_humidity: 45.0,
+        };
+        assert!(acquisition.approx_eq(&expected, 0.01, 0.01));
+        // Reading the sample consumed it
+        assert!(matches!(hts221.poll_read(), Err(nb::Error::WouldBlock)));
+    }
+
+    #[test]
+    fn ready_at_the_output_data_rate() {
+        let mut hts221 = continuous();
+        // The fake latches a sample every third status read
+        let ready: std::vec::Vec<bool> = (0..6).map(|_| hts221.poll_read().is_ok()).collect();
+        assert_eq!(ready.iter().filter(|ready| **ready).count(), 2);
+    }
+
+    #[test]
+    fn reports_bus_errors() {
+        let mut hts221 = continuous();
+        sensor(&mut hts221).fail_register = Some(STATUS);
+        assert!(matches!(
+            hts221.poll_read(),
+            Err(nb::Error::Other(Hts221Error::I2c(_)))
+        ));
+    }
+}
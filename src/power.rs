@@ -0,0 +1,191 @@
+use super::{Celsius, Hts221, Hts221Error, Interface, SensorAcquisition};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayUs;
+
+/// Time for the sensor to boot after its supply is switched on, in milliseconds.
+pub const POWER_ON_DELAY_MS: u32 = 5;
+
+/// Error returned by a [`PoweredHts221`].
+#[derive(Debug)]
+pub enum PowerError<E, P> {
+    /// Error from the driver.
+    Driver(Hts221Error<E>),
+    /// Error switching the power enable pin.
+    Pin(P),
+}
+
+impl<E, P> From<Hts221Error<E>> for PowerError<E, P> {
+    fn from(e: Hts221Error<E>) -> Self {
+        PowerError::Driver(e)
+    }
+}
+
+/// An HTS221 driver whose supply rail is gated by a power enable pin.
+///
+/// The sensor loses its configuration when powered off, so it is initialized again, and its
+/// calibration reloaded, every time it is powered on.
+pub struct PoweredHts221<I, P>
+where
+    I: Interface,
+    P: OutputPin,
+{
+    hts221: Hts221<I>,
+    pin: P,
+    powered: bool,
+}
+
+impl<I, P> PoweredHts221<I, P>
+where
+    I: Interface,
+    P: OutputPin,
+{
+    /// Create a powered driver. The sensor is assumed to be off until
+    /// [`power_on`](Self::power_on) is called.
+    pub fn new(hts221: Hts221<I>, pin: P) -> Self {
+        Self {
+            hts221,
+            pin,
+            powered: false,
+        }
+    }
+
+    /// Switch the sensor on, wait for it to boot and initialize it.
+    pub async fn power_on<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<(), PowerError<I::Error, P::Error>> {
+        self.pin.set_high().map_err(PowerError::Pin)?;
+        self.powered = true;
+        delay.delay_ms(POWER_ON_DELAY_MS).await;
+        self.hts221.initialize().await?;
+        Ok(())
+    }
+
+    /// Switch the sensor off. It must be powered on again before reading sensor values.
+    pub fn power_off(&mut self) -> Result<(), PowerError<I::Error, P::Error>> {
+        self.pin.set_low().map_err(PowerError::Pin)?;
+        self.powered = false;
+        self.hts221.calibration = None;
+        Ok(())
+    }
+
+    /// Whether the sensor is powered on.
+    pub fn is_powered(&self) -> bool {
+        self.powered
+    }
+
+    /// Power on the sensor if needed, wait for a sample and power it off again.
+    ///
+    /// The sensor is powered off even if powering on or the measurement fails.
+    pub async fn measure<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<SensorAcquisition<Celsius>, PowerError<I::Error, P::Error>> {
+        let result = self.measure_powered(delay).await;
+        let off = self.power_off();
+        let acquisition = result?;
+        off?;
+        Ok(acquisition)
+    }
+
+    async fn measure_powered<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+    ) -> Result<SensorAcquisition<Celsius>, PowerError<I::Error, P::Error>> {
+        if !self.powered {
+            self.power_on(delay).await?;
+        }
+        let odr = self.hts221.current_odr().await?;
+        Ok(self.hts221.wait_for_sample(odr, delay).await?)
+    }
+
+    /// The underlying driver, for reads while the sensor is powered on.
+    pub fn driver(&mut self) -> &mut Hts221<I> {
+        &mut self.hts221
+    }
+
+    /// Release the driver and the power enable pin.
+    pub fn release(self) -> (Hts221<I>, P) {
+        (self.hts221, self.pin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use core::convert::Infallible;
+    use embedded_hal::digital::ErrorType;
+
+    // Power enable pin recording the levels it was set to
+    #[derive(Default)]
+    struct Pin {
+        levels: std::vec::Vec<bool>,
+    }
+
+    impl ErrorType for Pin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for Pin {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            self.levels.push(false);
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            self.levels.push(true);
+            Ok(())
+        }
+    }
+
+    fn powered() -> PoweredHts221<FakeSensor, Pin> {
+        PoweredHts221::new(Hts221::with_interface(FakeSensor::new()), Pin::default())
+    }
+
+    fn levels(powered: PoweredHts221<FakeSensor, Pin>) -> std::vec::Vec<bool> {
+        powered.release().1.levels
+    }
+
+    #[test]
+    fn measure_powers_on_and_off() {
+        let mut powered = powered();
+        let mut delay = NoDelay::default();
+        sensor(powered.driver()).next_sample = Some((22.0, 45.0));
+        let acquisition = block_on(powered.measure(&mut delay)).unwrap();
+        let expected = SensorAcquisition {
+            temperature: 22.0.into(),
+            relative_humidity: 45.0,
+        };
+        assert!(acquisition.approx_eq(&expected, 0.01, 0.01));
+        assert!(delay.total_us >= POWER_ON_DELAY_MS as u64 * 1000);
+        assert!(!powered.is_powered());
+        assert!(powered.driver().calibration.is_none());
+        assert_eq!(levels(powered), [true, false]);
+    }
+
+    #[test]
+    fn measure_powers_off_after_failed_initialization() {
+        let mut powered = powered();
+        sensor(powered.driver()).fail_from = Some(0);
+        assert!(matches!(
+            block_on(powered.measure(&mut NoDelay::default())),
+            Err(PowerError::Driver(Hts221Error::I2c(_)))
+        ));
+        assert!(!powered.is_powered());
+        assert_eq!(levels(powered), [true, false]);
+    }
+
+    #[test]
+    fn measure_powers_off_after_failed_read() {
+        let mut powered = powered();
+        block_on(powered.power_on(&mut NoDelay::default())).unwrap();
+        sensor(powered.driver()).fail_register = Some(CTRL_REG1);
+        assert!(matches!(
+            block_on(powered.measure(&mut NoDelay::default())),
+            Err(PowerError::Driver(Hts221Error::I2c(_)))
+        ));
+        assert!(!powered.is_powered());
+        assert_eq!(levels(powered), [true, false]);
+    }
+}
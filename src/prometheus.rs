@@ -0,0 +1,197 @@
+#[cfg(feature = "stats")]
+use super::Stats;
+use super::{Celsius, SensorAcquisition, HAS_HUMIDITY, HAS_TEMPERATURE};
+use std::fmt::{Display, Write};
+
+// Name, type and help text of a metric family
+struct Family {
+    name: &'static str,
+    kind: &'static str,
+    help: &'static str,
+}
+
+const FAMILIES: [Family; 7] = [
+    Family {
+        name: "hts221_temperature_celsius",
+        kind: "gauge",
+        help: "Temperature in degrees celsius.",
+    },
+    Family {
+        name: "hts221_relative_humidity_percent",
+        kind: "gauge",
+        help: "Relative humidity in percent.",
+    },
+    Family {
+        name: "hts221_reads_total",
+        kind: "counter",
+        help: "Successful sensor reads.",
+    },
+    Family {
+        name: "hts221_i2c_errors_total",
+        kind: "counter",
+        help: "Failed I2C transactions.",
+    },
+    Family {
+        name: "hts221_retries_total",
+        kind: "counter",
+        help: "I2C transactions retried by the driver.",
+    },
+    Family {
+        name: "hts221_reinits_total",
+        kind: "counter",
+        help: "Repeated driver initializations.",
+    },
+    Family {
+        name: "hts221_resets_total",
+        kind: "counter",
+        help: "Spontaneous sensor resets detected.",
+    },
+];
+
+const TEMPERATURE: usize = 0;
+const HUMIDITY: usize = 1;
+#[cfg(feature = "stats")]
+const READS: usize = 2;
+#[cfg(feature = "stats")]
+const I2C_ERRORS: usize = 3;
+#[cfg(feature = "stats")]
+const RETRIES: usize = 4;
+#[cfg(feature = "stats")]
+const REINITS: usize = 5;
+#[cfg(feature = "stats")]
+const RESETS: usize = 6;
+
+/// Renders readings and driver counters in the Prometheus text exposition format.
+///
+/// Every sample is labeled with the bus and address of the sensor, so several sensors
+/// scraped by the same gateway can be told apart. Samples of all sensors are grouped by
+/// metric, under a single `# HELP` and `# TYPE` header each.
+#[derive(Default)]
+pub struct PrometheusMetrics {
+    samples: [String; FAMILIES.len()],
+}
+
+impl PrometheusMetrics {
+    /// Start rendering metrics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the temperature and relative humidity of an acquisition from the sensor at
+    /// `address` on `bus`, leaving out a channel compiled out with the `temperature-only`
+    /// or `humidity-only` feature.
+    pub fn reading(
+        &mut self,
+        bus: &str,
+        address: u8,
+        acquisition: &SensorAcquisition<Celsius>,
+    ) -> &mut Self {
+        if HAS_TEMPERATURE {
+            self.sample(TEMPERATURE, bus, address, acquisition.temperature.value);
+        }
+        if HAS_HUMIDITY {
+            self.sample(HUMIDITY, bus, address, acquisition.relative_humidity);
+        }
+        self
+    }
+
+    /// Add the health counters maintained by the driver of the sensor at `address` on
+    /// `bus`.
+    #[cfg(feature = "stats")]
+    pub fn stats(&mut self, bus: &str, address: u8, stats: &Stats) -> &mut Self {
+        self.sample(READS, bus, address, stats.reads);
+        self.sample(I2C_ERRORS, bus, address, stats.i2c_errors);
+        self.sample(RETRIES, bus, address, stats.retries);
+        self.sample(REINITS, bus, address, stats.reinits);
+        self.sample(RESETS, bus, address, stats.resets);
+        self
+    }
+
+    /// The rendered metrics.
+    pub fn finish(self) -> String {
+        let mut output = String::new();
+        for (family, samples) in FAMILIES.iter().zip(&self.samples) {
+            if samples.is_empty() {
+                continue;
+            }
+            // Writing to a String cannot fail
+            let _ = write!(
+                output,
+                "# HELP {name} {help}\n# TYPE {name} {kind}\n{samples}",
+                name = family.name,
+                help = family.help,
+                kind = family.kind,
+                samples = samples,
+            );
+        }
+        output
+    }
+
+    fn sample<V: Display>(&mut self, family: usize, bus: &str, address: u8, value: V) {
+        let samples = &mut self.samples[family];
+        let _ = write!(samples, "{}{{bus=\"", FAMILIES[family].name);
+        escape_label(samples, bus);
+        let _ = writeln!(samples, "\",address=\"0x{:02x}\"}} {}", address, value);
+    }
+}
+
+// Label values escape backslashes, double quotes and line feeds
+fn escape_label(out: &mut String, value: &str) {
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acquisition(temperature: f32, relative_humidity: f32) -> SensorAcquisition<Celsius> {
+        SensorAcquisition {
+            temperature: temperature.into(),
+            relative_humidity,
+        }
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+    fn groups_samples_under_one_header() {
+        let mut metrics = PrometheusMetrics::new();
+        metrics
+            .reading("/dev/i2c-1", 0x5F, &acquisition(21.5, 40.0))
+            .reading("/dev/i2c-2", 0x5F, &acquisition(22.5, 45.0));
+        assert_eq!(
+            metrics.finish(),
+            "# HELP hts221_temperature_celsius Temperature in degrees celsius.\n\
+             # TYPE hts221_temperature_celsius gauge\n\
+             hts221_temperature_celsius{bus=\"/dev/i2c-1\",address=\"0x5f\"} 21.5\n\
+             hts221_temperature_celsius{bus=\"/dev/i2c-2\",address=\"0x5f\"} 22.5\n\
+             # HELP hts221_relative_humidity_percent Relative humidity in percent.\n\
+             # TYPE hts221_relative_humidity_percent gauge\n\
+             hts221_relative_humidity_percent{bus=\"/dev/i2c-1\",address=\"0x5f\"} 40\n\
+             hts221_relative_humidity_percent{bus=\"/dev/i2c-2\",address=\"0x5f\"} 45\n"
+        );
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        let mut out = String::new();
+        escape_label(&mut out, "a\\b\"c\nd");
+        assert_eq!(out, "a\\\\b\\\"c\\nd");
+    }
+
+    #[test]
+    #[cfg(feature = "stats")]
+    fn renders_stats() {
+        let mut metrics = PrometheusMetrics::new();
+        metrics.stats("bus", 0x5F, &Stats::default());
+        let output = metrics.finish();
+        assert_eq!(output.matches("# TYPE").count(), 5);
+        assert!(output.contains("hts221_resets_total{bus=\"bus\",address=\"0x5f\"} 0\n"));
+    }
+}
@@ -0,0 +1,113 @@
+use super::{Celsius, Hts221, Interface, SensorAcquisition, HAS_HUMIDITY, HAS_TEMPERATURE};
+
+/// Lowest temperature of the specified operating range, in degrees celsius.
+pub const OPERATING_TEMPERATURE_MIN: f32 = -40.0;
+
+/// Highest temperature of the specified operating range, in degrees celsius.
+pub const OPERATING_TEMPERATURE_MAX: f32 = 120.0;
+
+/// Flags raised for values outside of the range the sensor can be trusted in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RangeFlags {
+    /// The temperature is outside of the specified operating range.
+    pub temperature_out_of_range: bool,
+    /// The relative humidity is outside of 0 to 100 %.
+    pub humidity_out_of_range: bool,
+    /// The temperature is outside of the calibration points, so it was extrapolated.
+    pub temperature_extrapolated: bool,
+    /// The relative humidity is outside of the calibration points, so it was extrapolated.
+    pub humidity_extrapolated: bool,
+}
+
+impl RangeFlags {
+    /// Whether no flag is raised.
+    pub fn is_valid(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl SensorAcquisition<Celsius> {
+    /// Flag values outside of the specified operating range of the sensor.
+    ///
+    /// Use [`Hts221::range_flags`] to also check the calibrated span. A channel compiled out
+    /// with the `temperature-only` or `humidity-only` feature is never flagged.
+    pub fn range_flags(&self) -> RangeFlags {
+        let temperature = self.temperature.value;
+        RangeFlags {
+            temperature_out_of_range: HAS_TEMPERATURE
+                && !(OPERATING_TEMPERATURE_MIN..=OPERATING_TEMPERATURE_MAX).contains(&temperature),
+            humidity_out_of_range: HAS_HUMIDITY && !(0.0..=100.0).contains(&self.relative_humidity),
+            ..RangeFlags::default()
+        }
+    }
+}
+
+impl<I> Hts221<I>
+where
+    I: Interface,
+{
+    /// Flag values of an acquisition outside of the specified operating range, or outside of
+    /// the span between the calibration points of this sensor.
+    pub fn range_flags(&self, acquisition: &SensorAcquisition<Celsius>) -> RangeFlags {
+        let mut flags = acquisition.range_flags();
+        if let Some(calibration) = &self.calibration {
+            #[cfg(not(feature = "humidity-only"))]
+            {
+                let t = &calibration.temperature;
+                flags.temperature_extrapolated = !(t.t0_degc.raw_value()..=t.t1_degc.raw_value())
+                    .contains(&acquisition.temperature.value);
+            }
+            #[cfg(not(feature = "temperature-only"))]
+            {
+                let h = &calibration.humidity;
+                flags.humidity_extrapolated =
+                    !(h.h0_rh..=h.h1_rh).contains(&acquisition.relative_humidity);
+            }
+        }
+        flags
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn acquisition(temperature: f32, relative_humidity: f32) -> SensorAcquisition<Celsius> {
+        SensorAcquisition {
+            temperature: temperature.into(),
+            relative_humidity,
+        }
+    }
+
+    #[test]
+    fn flags_values_outside_of_operating_range() {
+        assert!(acquisition(25.0, 50.0).range_flags().is_valid());
+        let flags = acquisition(-40.5, 100.5).range_flags();
+        assert_eq!(flags.temperature_out_of_range, HAS_TEMPERATURE);
+        assert_eq!(flags.humidity_out_of_range, HAS_HUMIDITY);
+    }
+
+    #[test]
+    fn flags_extrapolated_values() {
+        let hts221 = initialized();
+        assert!(hts221.range_flags(&acquisition(25.0, 50.0)).is_valid());
+        let flags = hts221.range_flags(&acquisition(31.0, 39.0));
+        assert_eq!(flags.temperature_extrapolated, HAS_TEMPERATURE);
+        assert_eq!(flags.humidity_extrapolated, HAS_HUMIDITY);
+        assert!(!flags.temperature_out_of_range);
+    }
+
+    #[test]
+    #[cfg(any(feature = "temperature-only", feature = "humidity-only"))]
+    fn ignores_missing_channel() {
+        let hts221 = initialized();
+        if HAS_HUMIDITY {
+            assert!(hts221.range_flags(&acquisition(f32::NAN, 50.0)).is_valid());
+        }
+        if HAS_TEMPERATURE {
+            assert!(hts221.range_flags(&acquisition(25.0, f32::NAN)).is_valid());
+        }
+    }
+}
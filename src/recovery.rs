@@ -0,0 +1,146 @@
+use super::{Celsius, Hts221, Hts221Error, Interface, SensorAcquisition};
+use embedded_hal_async::delay::DelayUs;
+
+/// Values read through a [`SaturationRecovery`] policy.
+#[derive(Clone, Debug)]
+pub struct RecoveryAcquisition {
+    /// Sensor values.
+    pub acquisition: SensorAcquisition<Celsius>,
+    /// Whether a recovery heater pulse ran recently, so the values may not have stabilized.
+    pub recovering: bool,
+}
+
+/// Policy running a heater pulse when the humidity stays saturated, as a condensed sensor
+/// otherwise reports unusable values for hours.
+pub struct SaturationRecovery {
+    threshold: f32,
+    saturated_reads: u32,
+    pulse_ms: u32,
+    settle_reads: u32,
+    saturated: u32,
+    settling: u32,
+}
+
+impl SaturationRecovery {
+    /// Create a policy pulsing the heater for `pulse_ms` after `saturated_reads` consecutive
+    /// readings at or above `threshold` relative humidity. The following `settle_reads`
+    /// readings are flagged as recovering.
+    ///
+    /// The saturation period is counted in readings, so it scales with the read interval.
+    /// A `saturated_reads` of zero is treated as one, so only saturated readings trigger a
+    /// pulse.
+    pub fn new(threshold: f32, saturated_reads: u32, pulse_ms: u32, settle_reads: u32) -> Self {
+        Self {
+            threshold,
+            saturated_reads: saturated_reads.max(1),
+            pulse_ms,
+            settle_reads,
+            saturated: 0,
+            settling: 0,
+        }
+    }
+
+    /// Read sensor values, running a recovery heater pulse when needed.
+    pub async fn read<I: Interface, D: DelayUs>(
+        &mut self,
+        hts221: &mut Hts221<I>,
+        delay: &mut D,
+    ) -> Result<RecoveryAcquisition, Hts221Error<I::Error>> {
+        let acquisition = hts221.read().await?;
+
+        let mut recovering = self.settling > 0;
+        self.settling = self.settling.saturating_sub(1);
+
+        if acquisition.relative_humidity >= self.threshold {
+            self.saturated = self.saturated.saturating_add(1);
+        } else {
+            self.saturated = 0;
+        }
+
+        if self.saturated >= self.saturated_reads {
+            hts221.heater_pulse(delay, self.pulse_ms).await?;
+            self.saturated = 0;
+            self.settling = self.settle_reads;
+            recovering = true;
+        }
+
+        Ok(RecoveryAcquisition {
+            acquisition,
+            recovering,
+        })
+    }
+
+    /// Whether readings are currently flagged as recovering.
+    pub fn recovering(&self) -> bool {
+        self.settling > 0
+    }
+}
+
+// Saturation is judged on the humidity channel
+#[cfg(all(test, not(feature = "temperature-only")))]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    const HEATER: u8 = 0b10;
+
+    fn pulses(hts221: &mut Hts221<FakeSensor>) -> usize {
+        sensor(hts221)
+            .writes
+            .iter()
+            .filter(|(register, value)| *register == CTRL_REG2 && value & HEATER != 0)
+            .count()
+    }
+
+    fn read(
+        recovery: &mut SaturationRecovery,
+        hts221: &mut Hts221<FakeSensor>,
+        relative_humidity: f32,
+    ) -> bool {
+        sensor(hts221).sample(25.0, relative_humidity);
+        block_on(recovery.read(hts221, &mut NoDelay::default()))
+            .unwrap()
+            .recovering
+    }
+
+    #[test]
+    fn pulses_after_consecutive_saturated_reads() {
+        let mut hts221 = initialized();
+        let mut recovery = SaturationRecovery::new(95.0, 3, 100, 2);
+        assert!(!read(&mut recovery, &mut hts221, 96.0));
+        assert!(!read(&mut recovery, &mut hts221, 97.0));
+        assert_eq!(pulses(&mut hts221), 0);
+        assert!(read(&mut recovery, &mut hts221, 96.0));
+        assert_eq!(pulses(&mut hts221), 1);
+        assert!(!sensor(&mut hts221).heater_on());
+
+        // Settling readings are flagged, then the counter starts over
+        assert!(read(&mut recovery, &mut hts221, 96.0));
+        assert!(read(&mut recovery, &mut hts221, 96.0));
+        assert!(!recovery.recovering());
+        assert_eq!(pulses(&mut hts221), 1);
+        assert!(read(&mut recovery, &mut hts221, 96.0));
+        assert_eq!(pulses(&mut hts221), 2);
+    }
+
+    #[test]
+    fn dry_read_resets_the_count() {
+        let mut hts221 = initialized();
+        let mut recovery = SaturationRecovery::new(95.0, 2, 100, 0);
+        for relative_humidity in [96.0, 60.0, 96.0, 60.0, 96.0] {
+            assert!(!read(&mut recovery, &mut hts221, relative_humidity));
+        }
+        assert_eq!(pulses(&mut hts221), 0);
+    }
+
+    #[test]
+    fn zero_saturated_reads_is_one() {
+        let mut hts221 = initialized();
+        let mut recovery = SaturationRecovery::new(95.0, 0, 100, 0);
+        read(&mut recovery, &mut hts221, 60.0);
+        read(&mut recovery, &mut hts221, 60.0);
+        assert_eq!(pulses(&mut hts221), 0);
+        read(&mut recovery, &mut hts221, 96.0);
+        assert_eq!(pulses(&mut hts221), 1);
+    }
+}
@@ -0,0 +1,255 @@
+use super::{Celsius, Hts221, Hts221Error, Interface, SensorAcquisition, Temperature};
+
+/// Error returned by a [`RedundantPair`], identifying the failing sensor.
+#[derive(Debug)]
+pub enum RedundantError<A, B> {
+    /// Error from the primary sensor.
+    Primary(Hts221Error<A>),
+    /// Error from the secondary sensor.
+    Secondary(Hts221Error<B>),
+    /// Errors from both sensors.
+    Both(Hts221Error<A>, Hts221Error<B>),
+}
+
+/// One of the sensors of a [`RedundantPair`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PairMember {
+    /// The primary sensor.
+    Primary,
+    /// The secondary sensor.
+    Secondary,
+}
+
+/// Values agreed on by a [`RedundantPair`].
+#[derive(Clone, Debug)]
+pub struct VotedAcquisition {
+    /// Mean of the values read from both sensors, or the values of the remaining sensor
+    /// when the other one failed.
+    pub acquisition: SensorAcquisition<Celsius>,
+    /// Whether the sensors differ beyond the configured tolerance.
+    pub diverged: bool,
+    /// The sensor which failed to read, so the values come from the other one alone.
+    pub degraded: Option<PairMember>,
+}
+
+/// Two HTS221 sensors read together for 1oo2 voting.
+pub struct RedundantPair<A, B>
+where
+    A: Interface,
+    B: Interface,
+{
+    primary: Hts221<A>,
+    secondary: Hts221<B>,
+    temperature_tolerance: f32,
+    humidity_tolerance: f32,
+}
+
+impl<A, B> RedundantPair<A, B>
+where
+    A: Interface,
+    B: Interface,
+{
+    /// Create a pair from two drivers, with the tolerated difference in degrees celsius
+    /// and relative humidity percentage points.
+    pub fn new(
+        primary: Hts221<A>,
+        secondary: Hts221<B>,
+        temperature_tolerance: f32,
+        humidity_tolerance: f32,
+    ) -> Self {
+        Self {
+            primary,
+            secondary,
+            temperature_tolerance,
+            humidity_tolerance,
+        }
+    }
+
+    /// Initialize both drivers. Must be run before reading sensor values.
+    ///
+    /// Both sensors are initialized even if the first one fails. If one sensor fails, the
+    /// pair keeps running on the other one and the failed sensor is returned, as
+    /// [`VotedAcquisition::degraded`] does for reads. Initialization only fails if both
+    /// sensors fail.
+    pub async fn initialize(
+        &mut self,
+    ) -> Result<Option<PairMember>, RedundantError<A::Error, B::Error>> {
+        match (
+            self.primary.initialize().await,
+            self.secondary.initialize().await,
+        ) {
+            (Ok(()), Ok(())) => Ok(None),
+            (Err(_), Ok(())) => Ok(Some(PairMember::Primary)),
+            (Ok(()), Err(_)) => Ok(Some(PairMember::Secondary)),
+            (Err(primary), Err(secondary)) => Err(RedundantError::Both(primary, secondary)),
+        }
+    }
+
+    /// Read both sensors and vote on the result.
+    ///
+    /// If one sensor fails, the values of the other one are returned, marked as degraded.
+    /// The read only fails if both sensors fail.
+    pub async fn read(&mut self) -> Result<VotedAcquisition, RedundantError<A::Error, B::Error>> {
+        let (primary, secondary) = match (self.primary.read().await, self.secondary.read().await) {
+            (Ok(primary), Ok(secondary)) => (primary, secondary),
+            (Err(_), Ok(secondary)) => return Ok(degraded(secondary, PairMember::Primary)),
+            (Ok(primary), Err(_)) => return Ok(degraded(primary, PairMember::Secondary)),
+            (Err(primary), Err(secondary)) => return Err(RedundantError::Both(primary, secondary)),
+        };
+
+        let diverged = !primary.approx_eq(
+            &secondary,
+            self.temperature_tolerance,
+            self.humidity_tolerance,
+        );
+
+        let temperature: Temperature<Celsius> =
+            ((primary.temperature + secondary.temperature) / 2.0).into();
+        Ok(VotedAcquisition {
+            acquisition: SensorAcquisition {
+                temperature,
+                relative_humidity: (primary.relative_humidity + secondary.relative_humidity) / 2.0,
+            },
+            diverged,
+            degraded: None,
+        })
+    }
+
+    /// Release both drivers.
+    pub fn release(self) -> (Hts221<A>, Hts221<B>) {
+        (self.primary, self.secondary)
+    }
+}
+
+fn degraded(acquisition: SensorAcquisition<Celsius>, failed: PairMember) -> VotedAcquisition {
+    VotedAcquisition {
+        acquisition,
+        diverged: false,
+        degraded: Some(failed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    fn pair(primary: (f32, f32), secondary: (f32, f32)) -> RedundantPair<FakeSensor, FakeSensor> {
+        let (mut a, mut b) = (initialized(), initialized());
+        sensor(&mut a).sample(primary.0, primary.1);
+        sensor(&mut b).sample(secondary.0, secondary.1);
+        RedundantPair::new(a, b, 0.5, 2.0)
+    }
+
+    fn fail(hts221: &mut Hts221<FakeSensor>) {
+        sensor(hts221).fail_from = Some(0);
+    }
+
+    fn uninitialized(fail_init: bool) -> Hts221<FakeSensor> {
+        let mut hts221 = Hts221::with_interface(FakeSensor::new());
+        if fail_init {
+            fail(&mut hts221);
+        }
+        hts221
+    }
+
+    #[test]
+    fn initializes_both() {
+        let mut pair = RedundantPair::new(uninitialized(false), uninitialized(false), 0.5, 2.0);
+        assert_eq!(block_on(pair.initialize()).unwrap(), None);
+    }
+
+    #[test]
+    fn initializes_secondary_after_failed_primary() {
+        let mut pair = RedundantPair::new(uninitialized(true), uninitialized(false), 0.5, 2.0);
+        assert_eq!(
+            block_on(pair.initialize()).unwrap(),
+            Some(PairMember::Primary)
+        );
+        sensor(&mut pair.secondary).sample(24.0, 60.0);
+        let voted = block_on(pair.read()).unwrap();
+        assert_eq!(voted.degraded, Some(PairMember::Primary));
+        let expected = SensorAcquisition {
+            temperature: 24.0.into(),
+            relative_humidity: 60.0,
+        };
+        assert!(voted.acquisition.approx_eq(&expected, 0.01, 0.01));
+    }
+
+    #[test]
+    fn reports_failed_secondary_initialization() {
+        let mut pair = RedundantPair::new(uninitialized(false), uninitialized(true), 0.5, 2.0);
+        assert_eq!(
+            block_on(pair.initialize()).unwrap(),
+            Some(PairMember::Secondary)
+        );
+    }
+
+    #[test]
+    fn initialization_fails_when_both_fail() {
+        let mut pair = RedundantPair::new(uninitialized(true), uninitialized(true), 0.5, 2.0);
+        assert!(matches!(
+            block_on(pair.initialize()),
+            Err(RedundantError::Both(
+                Hts221Error::I2c(_),
+                Hts221Error::I2c(_)
+            ))
+        ));
+    }
+
+    #[test]
+    fn votes_on_mean() {
+        let voted = block_on(pair((22.0, 50.0), (22.4, 51.0)).read()).unwrap();
+        let expected = SensorAcquisition {
+            temperature: 22.2.into(),
+            relative_humidity: 50.5,
+        };
+        assert!(voted.acquisition.approx_eq(&expected, 0.01, 0.01));
+        assert!(!voted.diverged);
+        assert_eq!(voted.degraded, None);
+    }
+
+    #[test]
+    #[cfg(not(feature = "humidity-only"))]
+    fn detects_divergence() {
+        let voted = block_on(pair((22.0, 50.0), (23.0, 50.0)).read()).unwrap();
+        assert!(voted.diverged);
+    }
+
+    #[test]
+    fn returns_surviving_sensor() {
+        let mut pair = pair((22.0, 50.0), (24.0, 60.0));
+        fail(&mut pair.primary);
+        let voted = block_on(pair.read()).unwrap();
+        let expected = SensorAcquisition {
+            temperature: 24.0.into(),
+            relative_humidity: 60.0,
+        };
+        assert!(voted.acquisition.approx_eq(&expected, 0.01, 0.01));
+        assert!(!voted.diverged);
+        assert_eq!(voted.degraded, Some(PairMember::Primary));
+    }
+
+    #[test]
+    fn reports_failed_secondary() {
+        let mut pair = pair((22.0, 50.0), (24.0, 60.0));
+        fail(&mut pair.secondary);
+        let voted = block_on(pair.read()).unwrap();
+        assert_eq!(voted.degraded, Some(PairMember::Secondary));
+    }
+
+    #[test]
+    fn fails_when_both_fail() {
+        let mut pair = pair((22.0, 50.0), (24.0, 60.0));
+        fail(&mut pair.primary);
+        fail(&mut pair.secondary);
+        assert!(matches!(
+            block_on(pair.read()),
+            Err(RedundantError::Both(
+                Hts221Error::I2c(_),
+                Hts221Error::I2c(_)
+            ))
+        ));
+    }
+}
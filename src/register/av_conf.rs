@@ -0,0 +1,88 @@
+use super::super::Interface;
+
+pub(crate) const AV_CONF: u8 = 0x10;
+
+// Number of internal samples averaged for each AVGT setting
+const TEMPERATURE_SAMPLES: [u16; 8] = [2, 4, 8, 16, 32, 64, 128, 256];
+
+// Number of internal samples averaged for each AVGH setting
+const HUMIDITY_SAMPLES: [u16; 8] = [4, 8, 16, 32, 64, 128, 256, 512];
+
+/// Humidity and temperature averaging configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AvConf {
+    temperature: u8,
+    humidity: u8,
+}
+
+impl AvConf {
+    /// Read the register from the device.
+    pub async fn read<I: Interface>(interface: &mut I) -> Result<AvConf, I::Error> {
+        let mut buf = [0; 1];
+        interface.read_registers(AV_CONF, &mut buf).await?;
+        Ok(buf[0].into())
+    }
+
+    /// Write the register to the device.
+    pub async fn write<I: Interface>(interface: &mut I, reg: AvConf) -> Result<(), I::Error> {
+        interface.write_register(AV_CONF, reg.into()).await
+    }
+
+    /// Read, modify and write back the register.
+    pub async fn modify<I: Interface, F: FnOnce(&mut AvConf)>(
+        interface: &mut I,
+        modify: F,
+    ) -> Result<(), I::Error> {
+        let mut reg = Self::read(interface).await?;
+        modify(&mut reg);
+        Self::write(interface, reg).await
+    }
+
+    /// Setting of temperature averaging, from 0 (2 samples) to 7 (256 samples).
+    pub fn temperature_average(&self) -> u8 {
+        self.temperature
+    }
+
+    /// Setting of humidity averaging, from 0 (4 samples) to 7 (512 samples).
+    pub fn humidity_average(&self) -> u8 {
+        self.humidity
+    }
+
+    /// Set temperature averaging, from 0 (2 samples) to 7 (256 samples).
+    pub fn set_temperature_average(&mut self, setting: u8) -> &mut Self {
+        self.temperature = setting & 0b111;
+        self
+    }
+
+    /// Set humidity averaging, from 0 (4 samples) to 7 (512 samples).
+    pub fn set_humidity_average(&mut self, setting: u8) -> &mut Self {
+        self.humidity = setting & 0b111;
+        self
+    }
+
+    /// Number of internal samples averaged per temperature output.
+    pub fn temperature_samples(&self) -> u16 {
+        TEMPERATURE_SAMPLES[(self.temperature & 0b111) as usize]
+    }
+
+    /// Number of internal samples averaged per humidity output.
+    pub fn humidity_samples(&self) -> u16 {
+        HUMIDITY_SAMPLES[(self.humidity & 0b111) as usize]
+    }
+}
+
+impl Into<AvConf> for u8 {
+    fn into(self) -> AvConf {
+        AvConf {
+            temperature: (self >> 3) & 0b111,
+            humidity: self & 0b111,
+        }
+    }
+}
+
+impl Into<u8> for AvConf {
+    fn into(self) -> u8 {
+        ((self.temperature & 0b111) << 3) | (self.humidity & 0b111)
+    }
+}
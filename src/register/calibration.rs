@@ -1,110 +1,312 @@
-use super::super::{Celsius, I2cAddress, Temperature};
-use embedded_hal_async::i2c::*;
+use super::super::Interface;
+#[cfg(not(feature = "humidity-only"))]
+use super::super::{Celsius, Temperature};
 
-// 16-byte block of calibration at 0x30 with high bit for auto-increment
-const CALIBRATION_16: u8 = 0xB0;
+// 16-byte block of calibration
+const CALIBRATION_16: u8 = 0x30;
 
+/// Calibration coefficients stored in the sensor.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Calibration {
+    /// Temperature calibration points.
+    #[cfg(not(feature = "humidity-only"))]
     pub temperature: TemperatureCalibration,
+    /// Humidity calibration points.
+    #[cfg(not(feature = "temperature-only"))]
     pub humidity: HumidityCalibration,
 }
 
 impl Calibration {
-    pub(crate) async fn read<I: I2c>(
-        address: I2cAddress,
-        i2c: &mut I,
-    ) -> Result<Calibration, I::Error> {
+    /// Read the calibration block from the device.
+    pub async fn read<I: Interface>(interface: &mut I) -> Result<Calibration, I::Error> {
         let mut buf = [0; 16];
-        let _ = i2c
-            .write_read(address.into(), &[CALIBRATION_16], &mut buf)
-            .await?;
+        interface.read_registers(CALIBRATION_16, &mut buf).await?;
         Ok(buf.into())
     }
 
+    /// Convert a raw temperature output into a calibrated temperature.
+    #[cfg(not(feature = "humidity-only"))]
     pub fn calibrated_temperature(&self, t_out: i16) -> Temperature<Celsius> {
         self.temperature.calibrated(t_out)
     }
 
+    /// Convert a raw humidity output into a calibrated relative humidity.
+    #[cfg(not(feature = "temperature-only"))]
     pub fn calibrated_humidity(&self, h_out: i16) -> f32 {
         self.humidity.calibrated(h_out)
     }
+
+    /// Check that the calibration points are distinct and ordered, so interpolation is sound.
+    pub fn is_valid(&self) -> bool {
+        #[cfg(not(feature = "humidity-only"))]
+        if !self.temperature.is_valid() {
+            return false;
+        }
+        #[cfg(not(feature = "temperature-only"))]
+        if !self.humidity.is_valid() {
+            return false;
+        }
+        true
+    }
 }
 
+/// Two-point temperature calibration.
+#[cfg(not(feature = "humidity-only"))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct TemperatureCalibration {
+    /// Raw output at the first calibration point.
     pub t0_out: i16,
+    /// Raw output at the second calibration point.
     pub t1_out: i16,
+    /// Temperature at the first calibration point.
     pub t0_degc: Temperature<Celsius>,
+    /// Temperature at the second calibration point.
     pub t1_degc: Temperature<Celsius>,
+    /// Degrees celsius per raw output unit.
     pub slope: f32,
 }
 
+#[cfg(not(feature = "humidity-only"))]
 impl TemperatureCalibration {
+    /// Interpolate a raw temperature output.
     pub fn calibrated(&self, t_out: i16) -> Temperature<Celsius> {
-        self.t0_degc + (self.slope * (t_out - self.t0_out) as f32)
+        self.t0_degc + self.slope * (t_out as f32 - self.t0_out as f32)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.t0_out != self.t1_out
+            && self.t0_degc.raw_value() < self.t1_degc.raw_value()
+            && self.slope.is_finite()
     }
 }
 
+/// Two-point humidity calibration.
+#[cfg(not(feature = "temperature-only"))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct HumidityCalibration {
+    /// Raw output at the first calibration point.
     pub h0_out: i16,
+    /// Raw output at the second calibration point.
     pub h1_out: i16,
+    /// Relative humidity at the first calibration point.
     pub h0_rh: f32,
+    /// Relative humidity at the second calibration point.
     pub h1_rh: f32,
+    /// Relative humidity percentage points per raw output unit.
     pub slope: f32,
 }
 
+#[cfg(not(feature = "temperature-only"))]
 impl HumidityCalibration {
+    /// Interpolate a raw humidity output.
     pub fn calibrated(&self, h_out: i16) -> f32 {
-        self.h0_rh + (self.slope * (h_out - self.h0_out) as f32)
+        self.h0_rh + self.slope * (h_out as f32 - self.h0_out as f32)
+    }
+
+    fn is_valid(&self) -> bool {
+        self.h0_out != self.h1_out && self.h0_rh < self.h1_rh && self.slope.is_finite()
     }
 }
 
 impl Into<Calibration> for [u8; 16] {
     fn into(self) -> Calibration {
-        let t0_out = i16::from_le_bytes([self[12], self[13]]);
+        Calibration {
+            #[cfg(not(feature = "humidity-only"))]
+            temperature: temperature_calibration(&self),
+            #[cfg(not(feature = "temperature-only"))]
+            humidity: humidity_calibration(&self),
+        }
+    }
+}
+
+#[cfg(not(feature = "humidity-only"))]
+fn temperature_calibration(block: &[u8; 16]) -> TemperatureCalibration {
+    let raw = RawCalibration::new(*block);
+    let (t0_out, t1_out) = (raw.t0_out(), raw.t1_out());
 
-        let t1_out = i16::from_le_bytes([self[14], self[15]]);
+    let t0_degc = (raw.t0_degc_x8() as f32 / 8.0).into();
+    let t1_degc = (raw.t1_degc_x8() as f32 / 8.0).into();
 
-        let t0_degc = self[2];
-        let t1_degc = self[3];
+    // Differences are taken in f32, as they may overflow i16
+    let slope = (t1_degc - t0_degc) / (t1_out as f32 - t0_out as f32);
 
-        let t_msb = self[5];
+    TemperatureCalibration {
+        t0_out,
+        t1_out,
+        t0_degc,
+        t1_degc,
+        slope,
+    }
+}
 
-        let t0_msb = (t_msb & 0b00000011) >> 2;
-        let t1_msb = (t_msb & 0b00001100) >> 2;
+#[cfg(not(feature = "temperature-only"))]
+fn humidity_calibration(block: &[u8; 16]) -> HumidityCalibration {
+    let raw = RawCalibration::new(*block);
+    let (h0_out, h1_out) = (raw.h0_out(), raw.h1_out());
 
-        let t0_degc = (i16::from_le_bytes([t0_degc, t0_msb]) as f32 / 8.0).into();
-        let t1_degc = (i16::from_le_bytes([t1_degc, t1_msb]) as f32 / 8.0).into();
+    let h0_rh = raw.h0_rh_x2() as f32 / 2.0;
+    let h1_rh = raw.h1_rh_x2() as f32 / 2.0;
 
-        let slope = (t1_degc - t0_degc) / ((t1_out - t0_out) as f32);
+    let slope = (h1_rh - h0_rh) / (h1_out as f32 - h0_out as f32);
 
-        let temperature = TemperatureCalibration {
-            t0_out,
-            t1_out,
-            t0_degc,
-            t1_degc,
-            slope,
-        };
+    HumidityCalibration {
+        h0_out,
+        h1_out,
+        h0_rh,
+        h1_rh,
+        slope,
+    }
+}
 
-        let h0_rh = self[0] as f32 / 2.0;
-        let h1_rh = self[1] as f32 / 2.0;
+/// Calibration block as stored in the sensor, interpolated with integer arithmetic.
+///
+/// All conversions are `const fn`, so reference vectors can be checked and lookup tables be
+/// precomputed at compile time from known calibration data.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RawCalibration([u8; 16]);
 
-        let h0_out = i16::from_le_bytes([self[6], self[7]]);
+impl RawCalibration {
+    /// Wrap a calibration block, as read from register 0x30 onwards.
+    pub const fn new(block: [u8; 16]) -> Self {
+        Self(block)
+    }
 
-        let h1_out = i16::from_le_bytes([self[10], self[11]]);
+    /// Read the calibration block from the device.
+    pub async fn read<I: Interface>(interface: &mut I) -> Result<Self, I::Error> {
+        let mut buf = [0; 16];
+        interface.read_registers(CALIBRATION_16, &mut buf).await?;
+        Ok(Self(buf))
+    }
 
-        let slope = (h1_rh - h0_rh) / ((h1_out - h0_out) as f32);
+    /// The calibration block.
+    pub const fn block(&self) -> [u8; 16] {
+        self.0
+    }
 
-        let humidity = HumidityCalibration {
-            h0_out,
-            h1_out,
-            h0_rh,
-            h1_rh,
-            slope,
-        };
+    /// Interpolate a raw temperature output in millidegrees celsius.
+    ///
+    /// Returns `None` if both calibration points have the same raw output.
+    #[cfg(not(feature = "humidity-only"))]
+    pub const fn millidegrees(&self, t_out: i16) -> Option<i32> {
+        interpolate(
+            self.t0_out(),
+            self.t0_degc_x8() as i64 * 125,
+            self.t1_out(),
+            self.t1_degc_x8() as i64 * 125,
+            t_out,
+        )
+    }
 
-        Calibration {
-            temperature,
-            humidity,
-        }
+    /// Interpolate a raw humidity output in thousandths of a percent relative humidity.
+    ///
+    /// Returns `None` if both calibration points have the same raw output.
+    #[cfg(not(feature = "temperature-only"))]
+    pub const fn millipercent(&self, h_out: i16) -> Option<i32> {
+        interpolate(
+            self.h0_out(),
+            self.h0_rh_x2() as i64 * 500,
+            self.h1_out(),
+            self.h1_rh_x2() as i64 * 500,
+            h_out,
+        )
+    }
+
+    #[cfg(not(feature = "humidity-only"))]
+    const fn t0_out(&self) -> i16 {
+        i16::from_le_bytes([self.0[12], self.0[13]])
+    }
+
+    #[cfg(not(feature = "humidity-only"))]
+    const fn t1_out(&self) -> i16 {
+        i16::from_le_bytes([self.0[14], self.0[15]])
+    }
+
+    // 10-bit values, with the two most significant bits of both in T1/T0 msb
+    #[cfg(not(feature = "humidity-only"))]
+    const fn t0_degc_x8(&self) -> u16 {
+        u16::from_le_bytes([self.0[2], self.0[5] & 0b0000_0011])
+    }
+
+    #[cfg(not(feature = "humidity-only"))]
+    const fn t1_degc_x8(&self) -> u16 {
+        u16::from_le_bytes([self.0[3], (self.0[5] & 0b0000_1100) >> 2])
+    }
+
+    #[cfg(not(feature = "temperature-only"))]
+    const fn h0_out(&self) -> i16 {
+        i16::from_le_bytes([self.0[6], self.0[7]])
+    }
+
+    #[cfg(not(feature = "temperature-only"))]
+    const fn h1_out(&self) -> i16 {
+        i16::from_le_bytes([self.0[10], self.0[11]])
+    }
+
+    #[cfg(not(feature = "temperature-only"))]
+    const fn h0_rh_x2(&self) -> u8 {
+        self.0[0]
+    }
+
+    #[cfg(not(feature = "temperature-only"))]
+    const fn h1_rh_x2(&self) -> u8 {
+        self.0[1]
+    }
+}
+
+// Linear interpolation through (x0, y0) and (x1, y1), rounded towards zero and saturated
+// to i32. Intermediates fit in i64 for any i16 outputs and the scaled calibration values.
+const fn interpolate(x0: i16, y0: i64, x1: i16, y1: i64, x: i16) -> Option<i32> {
+    let dx = x1 as i64 - x0 as i64;
+    if dx == 0 {
+        return None;
+    }
+    let y = y0 + (y1 - y0) * (x as i64 - x0 as i64) / dx;
+    Some(if y > i32::MAX as i64 {
+        i32::MAX
+    } else if y < i32::MIN as i64 {
+        i32::MIN
+    } else {
+        y as i32
+    })
+}
+
+// Reference vector: 20 to 30 °C over raw 0 to 1000, 40 to 70 %rH over raw 0 to 6000
+const REFERENCE: RawCalibration = RawCalibration::new([
+    80, 140, 160, 240, 0, 0, 0, 0, 0, 0, 0x70, 0x17, 0, 0, 0xe8, 0x03,
+]);
+#[cfg(not(feature = "humidity-only"))]
+const _: () = assert!(matches!(REFERENCE.millidegrees(500), Some(25_000)));
+#[cfg(not(feature = "temperature-only"))]
+const _: () = assert!(matches!(REFERENCE.millipercent(3000), Some(55_000)));
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_equal_calibration_points() {
+        let mut block = REFERENCE.block();
+        block[14..16].copy_from_slice(&0i16.to_le_bytes());
+        block[10..12].copy_from_slice(&0i16.to_le_bytes());
+        let calibration = RawCalibration::new(block);
+        #[cfg(not(feature = "humidity-only"))]
+        assert_eq!(calibration.millidegrees(500), None);
+        #[cfg(not(feature = "temperature-only"))]
+        assert_eq!(calibration.millipercent(3000), None);
+    }
+
+    #[test]
+    fn extrapolates_outside_of_calibration_points() {
+        #[cfg(not(feature = "humidity-only"))]
+        assert_eq!(REFERENCE.millidegrees(-1000), Some(10_000));
+        #[cfg(not(feature = "temperature-only"))]
+        assert_eq!(REFERENCE.millipercent(i16::MAX), Some(203_835));
+    }
+
+    #[test]
+    fn saturates_result() {
+        assert_eq!(interpolate(0, 0, 1, i32::MAX as i64, 2), Some(i32::MAX));
+        assert_eq!(interpolate(0, 0, 1, i32::MIN as i64, 2), Some(i32::MIN));
     }
 }
@@ -1,107 +1,142 @@
-use super::super::I2cAddress;
-use embedded_hal_async::i2c::*;
+use super::super::Interface;
 
-const CTRL_REG1: u8 = 0x20;
+pub(crate) const CTRL_REG1: u8 = 0x20;
 
-#[derive(Debug, Copy, Clone)]
-enum Power {
+/// Power mode of the device.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerMode {
+    /// Power-down mode, no samples are produced.
     PowerDown,
+    /// Active mode.
     Active,
 }
 
+/// Update mode of the output registers.
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum BlockDataUpdate {
+    /// Output registers are updated continuously.
     Continuous,
+    /// Output registers are not updated until both bytes have been read.
     MsbLsbReading,
 }
 
-#[derive(Debug, Copy, Clone)]
+/// Rate at which new samples are produced.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum OutputDataRate {
+    /// Samples are only produced on request.
     OneShot,
+    /// 1 Hz.
     Hz1,
+    /// 7 Hz.
     Hz7,
+    /// 12.5 Hz.
     Hz12p5,
 }
 
+impl OutputDataRate {
+    /// Time between samples in milliseconds, or `None` for one-shot mode.
+    pub fn period_ms(&self) -> Option<u32> {
+        match self {
+            OutputDataRate::OneShot => None,
+            OutputDataRate::Hz1 => Some(1000),
+            OutputDataRate::Hz7 => Some(143),
+            OutputDataRate::Hz12p5 => Some(80),
+        }
+    }
+}
+
+/// Control register 1.
 #[derive(Debug, Copy, Clone)]
-pub(crate) struct Ctrl1 {
-    power_down: Power,
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Ctrl1 {
+    power_down: PowerMode,
     block_data_update: BlockDataUpdate,
     output_data_rate: OutputDataRate,
 }
 
 impl Ctrl1 {
-    pub async fn read<I: I2c>(address: I2cAddress, i2c: &mut I) -> Result<Ctrl1, I::Error> {
+    /// Read the register from the device.
+    pub async fn read<I: Interface>(interface: &mut I) -> Result<Ctrl1, I::Error> {
         let mut buf = [0; 1];
-        let _ = i2c
-            .write_read(address.into(), &[CTRL_REG1], &mut buf)
-            .await?;
+        interface.read_registers(CTRL_REG1, &mut buf).await?;
         Ok(buf[0].into())
     }
 
-    pub async fn write<I: I2c>(
-        address: I2cAddress,
-        i2c: &mut I,
-        reg: Ctrl1,
-    ) -> Result<(), I::Error> {
-        let bytes = [CTRL_REG1, reg.into()];
-        Ok(i2c.write(address.into(), &bytes).await?)
+    /// Write the register to the device.
+    pub async fn write<I: Interface>(interface: &mut I, reg: Ctrl1) -> Result<(), I::Error> {
+        interface.write_register(CTRL_REG1, reg.into()).await
     }
 
-    pub async fn modify<I: I2c, F: FnOnce(&mut Ctrl1)>(
-        address: I2cAddress,
-        i2c: &mut I,
+    /// Read, modify and write back the register.
+    pub async fn modify<I: Interface, F: FnOnce(&mut Ctrl1)>(
+        interface: &mut I,
         modify: F,
     ) -> Result<(), I::Error> {
-        let mut reg = Self::read(address.into(), i2c).await?;
+        let mut reg = Self::read(interface).await?;
         modify(&mut reg);
-        Self::write(address.into(), i2c, reg).await
+        Self::write(interface, reg).await
     }
 
-    #[allow(dead_code)]
+    /// Put the device in power-down mode.
     pub fn power_down(&mut self) -> &Self {
-        self.power_down = Power::PowerDown;
+        self.power_down = PowerMode::PowerDown;
         self
     }
 
+    /// Put the device in active mode.
     pub fn power_active(&mut self) -> &mut Self {
-        self.power_down = Power::Active;
+        self.power_down = PowerMode::Active;
         self
     }
 
+    /// Set the output data rate.
     pub fn output_data_rate(&mut self, odr: OutputDataRate) -> &mut Self {
         self.output_data_rate = odr;
         self
     }
 
+    /// Set the block data update mode.
     pub fn block_data_update(&mut self, bdu: BlockDataUpdate) -> &mut Self {
         self.block_data_update = bdu;
         self
     }
+
+    /// Current power mode.
+    pub fn power_mode(&self) -> PowerMode {
+        self.power_down
+    }
+
+    /// Current output data rate.
+    pub fn current_output_data_rate(&self) -> OutputDataRate {
+        self.output_data_rate
+    }
 }
 
-impl Into<Power> for u8 {
-    fn into(self) -> Power {
+impl Into<PowerMode> for u8 {
+    fn into(self) -> PowerMode {
         if (self & 0x80) != 0 {
-            Power::Active
+            PowerMode::Active
         } else {
-            Power::PowerDown
+            PowerMode::PowerDown
         }
     }
 }
 
-impl From<Power> for u8 {
-    fn from(p: Power) -> Self {
+impl From<PowerMode> for u8 {
+    fn from(p: PowerMode) -> Self {
         match p {
-            Power::PowerDown => 0b00000000,
-            Power::Active => 0b10000000,
+            PowerMode::PowerDown => 0b00000000,
+            PowerMode::Active => 0b10000000,
         }
     }
 }
 
 impl Into<BlockDataUpdate> for u8 {
     fn into(self) -> BlockDataUpdate {
-        if (self & 0x40) != 0 {
+        if (self & 0b100) != 0 {
             BlockDataUpdate::MsbLsbReading
         } else {
             BlockDataUpdate::Continuous
@@ -1,9 +1,10 @@
-use super::super::I2cAddress;
-use embedded_hal_async::i2c::*;
+use super::super::Interface;
 
 const CTRL_REG2: u8 = 0x21;
 
+/// Control register 2.
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Ctrl2 {
     boot: bool,
     heater: bool,
@@ -11,46 +12,55 @@ pub struct Ctrl2 {
 }
 
 impl Ctrl2 {
-    pub(crate) async fn read<I: I2c>(address: I2cAddress, i2c: &mut I) -> Result<Ctrl2, I::Error> {
+    /// Read the register from the device.
+    pub async fn read<I: Interface>(interface: &mut I) -> Result<Ctrl2, I::Error> {
         let mut buf = [0; 1];
-        let _ = i2c
-            .write_read(address.into(), &[CTRL_REG2], &mut buf)
-            .await?;
+        interface.read_registers(CTRL_REG2, &mut buf).await?;
         Ok(buf[0].into())
     }
 
-    pub(crate) async fn write<I: I2c>(
-        address: I2cAddress,
-        i2c: &mut I,
-        reg: Ctrl2,
-    ) -> Result<(), I::Error> {
-        Ok(i2c.write(address.into(), &[CTRL_REG2, reg.into()]).await?)
+    /// Write the register to the device.
+    pub async fn write<I: Interface>(interface: &mut I, reg: Ctrl2) -> Result<(), I::Error> {
+        interface.write_register(CTRL_REG2, reg.into()).await
     }
 
-    pub(crate) async fn modify<I: I2c, F: FnOnce(&mut Ctrl2)>(
-        address: I2cAddress,
-        i2c: &mut I,
+    /// Read, modify and write back the register.
+    pub async fn modify<I: Interface, F: FnOnce(&mut Ctrl2)>(
+        interface: &mut I,
         modify: F,
     ) -> Result<(), I::Error> {
-        let mut reg = Self::read(address.into(), i2c).await?;
+        let mut reg = Self::read(interface).await?;
         modify(&mut reg);
-        Self::write(address.into(), i2c, reg).await
+        Self::write(interface, reg).await
     }
 
+    /// Reboot the memory content, reloading the calibration.
     pub fn boot(&mut self) -> &mut Self {
         self.boot = true;
         self
     }
 
+    /// Switch the internal heater on or off.
     pub fn heater(&mut self, on: bool) -> &mut Self {
         self.heater = on;
         self
     }
 
+    /// Whether the internal heater is on.
+    pub fn heater_enabled(&self) -> bool {
+        self.heater
+    }
+
+    /// Start a one-shot acquisition.
     pub fn enable_one_shot(&mut self) -> &mut Self {
         self.enable_one_shot = true;
         self
     }
+
+    /// Whether a one-shot acquisition is in progress. The device clears the bit when done.
+    pub fn one_shot_pending(&self) -> bool {
+        self.enable_one_shot
+    }
 }
 
 impl Into<Ctrl2> for u8 {
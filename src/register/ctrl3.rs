@@ -1,63 +1,75 @@
-use super::super::I2cAddress;
-use embedded_hal_async::i2c::*;
+use super::super::Interface;
 
-const CTRL_REG3: u8 = 0x22;
+pub(crate) const CTRL_REG3: u8 = 0x22;
 
-#[derive(Debug, Copy, Clone)]
+/// Output type of the data ready pin.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ReadyMode {
+    /// Push-pull output.
     PushPull,
+    /// Open drain output.
     OpenDrain,
 }
 
-#[derive(Debug, Copy, Clone)]
+/// Active level of the data ready pin.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum ActiveState {
+    /// Active high.
     High,
+    /// Active low.
     Low,
 }
 
+/// Control register 3.
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct Ctrl3 {
+    /// Active level of the data ready pin.
     pub active: ActiveState,
+    /// Output type of the data ready pin.
     pub mode: ReadyMode,
+    /// Whether the data ready signal is enabled.
     pub enable: bool,
 }
 
 impl Ctrl3 {
-    pub(crate) async fn read<I: I2c>(address: I2cAddress, i2c: &mut I) -> Result<Ctrl3, I::Error> {
+    /// Read the register from the device.
+    pub async fn read<I: Interface>(interface: &mut I) -> Result<Ctrl3, I::Error> {
         let mut buf = [0; 1];
-        let _ = i2c
-            .write_read(address.into(), &[CTRL_REG3], &mut buf)
-            .await?;
+        interface.read_registers(CTRL_REG3, &mut buf).await?;
         Ok(buf[0].into())
     }
 
-    pub(crate) async fn write<I: I2c>(
-        address: I2cAddress,
-        i2c: &mut I,
-        reg: Ctrl3,
-    ) -> Result<(), I::Error> {
-        Ok(i2c.write(address.into(), &[CTRL_REG3, reg.into()]).await?)
+    /// Write the register to the device.
+    pub async fn write<I: Interface>(interface: &mut I, reg: Ctrl3) -> Result<(), I::Error> {
+        interface.write_register(CTRL_REG3, reg.into()).await
     }
 
-    pub(crate) async fn modify<I: I2c, F: FnOnce(&mut Ctrl3)>(
-        address: I2cAddress,
-        i2c: &mut I,
+    /// Read, modify and write back the register.
+    pub async fn modify<I: Interface, F: FnOnce(&mut Ctrl3)>(
+        interface: &mut I,
         modify: F,
     ) -> Result<(), I::Error> {
-        let mut reg = Self::read(address, i2c).await?;
+        let mut reg = Self::read(interface).await?;
         modify(&mut reg);
-        Self::write(address, i2c, reg).await
+        Self::write(interface, reg).await
     }
 
+    /// Set the active level of the data ready pin.
     pub fn active_state(&mut self, active_state: ActiveState) -> &mut Self {
         self.active = active_state;
         self
     }
 
+    /// Enable or disable the data ready signal.
     pub fn enable(&mut self, enable: bool) -> &mut Self {
         self.enable = enable;
         self
     }
 
+    /// Set the output type of the data ready pin.
     pub fn ready_mode(&mut self, ready_mode: ReadyMode) -> &mut Self {
         self.mode = ready_mode;
         self
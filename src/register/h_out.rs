@@ -1,15 +1,16 @@
-use super::super::I2cAddress;
-use embedded_hal_async::i2c::*;
+use super::super::Interface;
 
-// auto-increment variant of 2 bytes
-const H_OUT: u8 = 0xA8;
+// 2 bytes, little-endian
+const H_OUT: u8 = 0x28;
 
+/// Raw humidity output register.
 pub struct Hout;
 
 impl Hout {
-    pub(crate) async fn read<I: I2c>(address: I2cAddress, i2c: &mut I) -> Result<i16, I::Error> {
+    /// Read the raw humidity output.
+    pub async fn read<I: Interface>(interface: &mut I) -> Result<i16, I::Error> {
         let mut buf = [0; 2];
-        let _ = i2c.write_read(address.into(), &[H_OUT], &mut buf).await?;
+        interface.read_registers(H_OUT, &mut buf).await?;
         Ok(i16::from_le_bytes(buf))
     }
 }
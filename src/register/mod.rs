@@ -1,8 +1,25 @@
+//! Typed access to the HTS221 registers.
+//!
+//! Each register type reads and writes its register through an [`Interface`](crate::Interface),
+//! for composing custom sequences that the driver does not provide.
+
+/// AV_CONF: humidity and temperature averaging.
+pub mod av_conf;
+/// Factory calibration coefficients.
 pub mod calibration;
+/// CTRL_REG1: power, block data update and output data rate.
 pub mod ctrl1;
+/// CTRL_REG2: reboot, heater and one-shot.
 pub mod ctrl2;
+/// CTRL_REG3: data ready signal configuration.
 pub mod ctrl3;
+/// HUMIDITY_OUT: raw humidity output.
+#[cfg(not(feature = "temperature-only"))]
 pub mod h_out;
+/// STATUS_REG: data availability.
 pub mod status;
+/// TEMP_OUT: raw temperature output.
+#[cfg(not(feature = "humidity-only"))]
 pub mod t_out;
+/// WHO_AM_I: device identification.
 pub mod who_am_i;
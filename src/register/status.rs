@@ -1,39 +1,49 @@
-use super::super::I2cAddress;
-use embedded_hal_async::i2c::*;
+use super::super::Interface;
 
 const STATUS: u8 = 0x27;
 
-pub(crate) struct Status {
+/// Status register, indicating which new samples are available.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Status {
+    raw: u8,
     temperature_available: bool,
     humidity_available: bool,
 }
 
 impl Status {
-    pub async fn read<I: I2c>(address: I2cAddress, i2c: &mut I) -> Result<Status, I::Error> {
+    /// Read the register from the device.
+    pub async fn read<I: Interface>(interface: &mut I) -> Result<Status, I::Error> {
         let mut buf = [0; 1];
-        let _ = i2c.write_read(address.into(), &[STATUS], &mut buf).await?;
+        interface.read_registers(STATUS, &mut buf).await?;
         Ok(buf[0].into())
     }
 
-    #[allow(dead_code)]
+    /// Whether a new temperature sample is available.
     pub fn temperature_available(&self) -> bool {
         self.temperature_available
     }
 
-    #[allow(dead_code)]
+    /// Whether a new humidity sample is available.
     pub fn humidity_available(&self) -> bool {
         self.humidity_available
     }
 
-    #[allow(dead_code)]
+    /// Whether any new sample is available.
     pub fn any_available(&self) -> bool {
         self.temperature_available || self.humidity_available
     }
+
+    /// Raw value of the register.
+    pub fn raw(&self) -> u8 {
+        self.raw
+    }
 }
 
 impl Into<Status> for u8 {
     fn into(self) -> Status {
         Status {
+            raw: self,
             temperature_available: ((self & 0b01) != 0),
             humidity_available: ((self & 0b10) != 0),
         }
@@ -1,15 +1,16 @@
-use super::super::I2cAddress;
-use embedded_hal_async::i2c::*;
+use super::super::Interface;
 
-// auto-increment variant of 2 bytes
-const T_OUT: u8 = 0xAA;
+// 2 bytes, little-endian
+const T_OUT: u8 = 0x2A;
 
+/// Raw temperature output register.
 pub struct Tout;
 
 impl Tout {
-    pub(crate) async fn read<I: I2c>(address: I2cAddress, i2c: &mut I) -> Result<i16, I::Error> {
+    /// Read the raw temperature output.
+    pub async fn read<I: Interface>(interface: &mut I) -> Result<i16, I::Error> {
         let mut buf = [0; 2];
-        let _ = i2c.write_read(address.into(), &[T_OUT], &mut buf).await?;
+        interface.read_registers(T_OUT, &mut buf).await?;
         Ok(i16::from_le_bytes(buf))
     }
 }
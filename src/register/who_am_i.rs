@@ -1,17 +1,18 @@
-#![allow(dead_code)]
-use super::super::I2cAddress;
-use embedded_hal_async::i2c::*;
+use super::super::Interface;
 
 const WHO_AM_I: u8 = 0x0F;
 
-pub(crate) struct WhoAmI;
+/// Device identification value of the HTS221.
+pub const HTS221_ID: u8 = 0xBC;
+
+/// Device identification register.
+pub struct WhoAmI;
 
 impl WhoAmI {
-    pub async fn read<I: I2c>(address: I2cAddress, i2c: &mut I) -> Result<I2cAddress, I::Error> {
+    /// Read the device identification value.
+    pub async fn read<I: Interface>(interface: &mut I) -> Result<u8, I::Error> {
         let mut buf = [0; 1];
-        let _ = i2c
-            .write_read(address.into(), &[WHO_AM_I], &mut buf)
-            .await?;
-        Ok(buf[0].into())
+        interface.read_registers(WHO_AM_I, &mut buf).await?;
+        Ok(buf[0])
     }
 }
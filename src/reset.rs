@@ -0,0 +1,104 @@
+use super::{
+    register::ctrl1::{Ctrl1, CTRL_REG1},
+    ErrorEvent, Hts221, Hts221Error, Hts221ErrorKind, Interface, PowerMode,
+};
+
+impl<I> Hts221<I>
+where
+    I: Interface,
+{
+    /// Check for a spontaneous reset of the sensor every `reads` sensor reads, or never if 0.
+    ///
+    /// A reset is detected by the power mode or output data rate not matching the
+    /// configuration, see [`check_reset`](Self::check_reset).
+    pub fn set_reset_check_interval(&mut self, reads: u32) {
+        self.reset_check_interval = reads;
+        self.reads_since_check = 0;
+    }
+
+    /// Check whether the sensor was reset underneath the driver, and configure it again if so.
+    ///
+    /// Returns whether a reset was detected. A detected reset is counted in the stats and
+    /// reported to the error hook as [`Hts221ErrorKind::DeviceReset`].
+    pub async fn check_reset(&mut self) -> Result<bool, Hts221Error<I::Error>> {
+        self.end_cancelled_pulse().await?;
+        let result = self.detect_reset().await;
+        self.track(result)
+    }
+
+    pub(crate) async fn reset_check_due(&mut self) -> Result<(), Hts221Error<I::Error>> {
+        if self.reset_check_interval == 0 || self.calibration.is_none() {
+            return Ok(());
+        }
+        self.reads_since_check += 1;
+        if self.reads_since_check >= self.reset_check_interval {
+            self.reads_since_check = 0;
+            self.check_reset().await?;
+        }
+        Ok(())
+    }
+
+    async fn detect_reset(&mut self) -> Result<bool, Hts221Error<I::Error>> {
+        let ctrl1 = Ctrl1::read(&mut self.interface).await?;
+        let config = self.config;
+        if ctrl1.power_mode() == PowerMode::Active
+            && ctrl1.current_output_data_rate() == config.output_data_rate
+        {
+            return Ok(false);
+        }
+
+        #[cfg(feature = "stats")]
+        {
+            self.stats.resets = self.stats.resets.saturating_add(1);
+        }
+        if let Some(hook) = self.error_hook {
+            hook(&ErrorEvent {
+                kind: Hts221ErrorKind::DeviceReset,
+                register: Some(CTRL_REG1),
+            });
+        }
+        self.write_ctrl1(&config).await?;
+        self.write_ctrl3(&config).await?;
+        self.write_av_conf(&config).await?;
+        // The reset switched the heater off
+        self.heater_switched(false);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+
+    fn reset(sensor: &mut FakeSensor) {
+        sensor.regs[CTRL_REG1 as usize] = 0;
+    }
+
+    fn configured(sensor: &mut FakeSensor) -> bool {
+        sensor.regs[CTRL_REG1 as usize] & 0x80 != 0
+    }
+
+    #[test]
+    fn checks_on_every_read_path() {
+        let mut hts221 = initialized();
+        hts221.set_reset_check_interval(1);
+
+        reset(sensor(&mut hts221));
+        block_on(hts221.read()).unwrap();
+        assert!(configured(sensor(&mut hts221)));
+
+        #[cfg(not(feature = "humidity-only"))]
+        {
+            reset(sensor(&mut hts221));
+            block_on(hts221.read_temperature()).unwrap();
+            assert!(configured(sensor(&mut hts221)));
+        }
+
+        #[cfg(not(feature = "temperature-only"))]
+        {
+            reset(sensor(&mut hts221));
+            block_on(hts221.read_humidity()).unwrap();
+            assert!(configured(sensor(&mut hts221)));
+        }
+    }
+}
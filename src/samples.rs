@@ -0,0 +1,147 @@
+use super::{Celsius, Hts221, Hts221Error, Interface, OutputDataRate, SensorAcquisition};
+use embedded_hal_async::delay::DelayUs;
+
+/// Callback invoked with each new sample, see [`Samples::set_listener`].
+pub type SampleListener = fn(&SensorAcquisition<Celsius>);
+
+/// Async iterator over consecutive acquisitions at the configured output data rate.
+///
+/// Created by [`Hts221::samples`]; each call to [`next_sample`](Self::next_sample) waits for
+/// the next sample to be ready.
+pub struct Samples<'a, I, D>
+where
+    I: Interface,
+    D: DelayUs,
+{
+    hts221: &'a mut Hts221<I>,
+    delay: D,
+    odr: Option<OutputDataRate>,
+    listener: Option<SampleListener>,
+}
+
+impl<'a, I, D> Samples<'a, I, D>
+where
+    I: Interface,
+    D: DelayUs,
+{
+    pub(crate) fn new(hts221: &'a mut Hts221<I>, delay: D) -> Self {
+        Self {
+            hts221,
+            delay,
+            odr: None,
+            listener: None,
+        }
+    }
+
+    /// Register a listener invoked with every sample read, for pushing samples to tasks
+    /// which do not own the driver.
+    pub fn set_listener(&mut self, listener: SampleListener) {
+        self.listener.replace(listener);
+    }
+
+    /// Wait for and read the next sample.
+    pub async fn next_sample(
+        &mut self,
+    ) -> Result<SensorAcquisition<Celsius>, Hts221Error<I::Error>> {
+        let odr = match self.odr {
+            Some(odr) => odr,
+            None => *self.odr.insert(self.hts221.current_odr().await?),
+        };
+        let sample = self.hts221.wait_for_sample(odr, &mut self.delay).await?;
+        if let Some(listener) = self.listener {
+            listener(&sample);
+        }
+        Ok(sample)
+    }
+
+    /// Nominal interval between samples at the output data rate, known once the first sample
+    /// was read. `None` in one-shot mode, where samples are taken on request.
+    ///
+    /// This is the period of the output data rate, not a measurement: the actual spacing of
+    /// samples drifts with the sensor's internal oscillator.
+    #[cfg(feature = "fugit")]
+    pub fn nominal_interval(&self) -> Option<fugit::MillisDurationU32> {
+        self.odr
+            .and_then(|odr| odr.period_ms())
+            .map(fugit::MillisDurationU32::from_ticks)
+    }
+
+    /// Read samples continuously, passing each one to the listener, until an error occurs.
+    pub async fn run(&mut self) -> Hts221Error<I::Error> {
+        loop {
+            if let Err(e) = self.next_sample().await {
+                return e;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use core::cell::Cell;
+
+    std::thread_local! {
+        static HEARD: Cell<usize> = const { Cell::new(0) };
+    }
+
+    fn at_7hz() -> Hts221<FakeSensor> {
+        let mut hts221 = initialized();
+        let mut config = hts221.config();
+        config.output_data_rate = OutputDataRate::Hz7;
+        block_on(hts221.reconfigure(config)).unwrap();
+        hts221
+    }
+
+    #[test]
+    fn yields_consecutive_samples() {
+        let mut hts221 = at_7hz();
+        sensor(&mut hts221).next_sample = Some((22.0, 45.0));
+        let mut samples = hts221.samples(NoDelay::default());
+        let expected = SensorAcquisition {
+            temperature: 22.0.into(),
+            relative_humidity: 45.0,
+        };
+        for _ in 0..3 {
+            let sample = block_on(samples.next_sample()).unwrap();
+            assert!(sample.approx_eq(&expected, 0.01, 0.01));
+        }
+    }
+
+    #[test]
+    fn reads_output_data_rate_once() {
+        let mut hts221 = at_7hz();
+        let mut samples = hts221.samples(NoDelay::default());
+        block_on(samples.next_sample()).unwrap();
+        sensor(samples.hts221).fail_register = Some(CTRL_REG1);
+        block_on(samples.next_sample()).unwrap();
+    }
+
+    #[test]
+    fn run_feeds_listener_until_error() {
+        let mut hts221 = at_7hz();
+        let mut samples = hts221.samples(NoDelay::default());
+        samples.set_listener(|_| HEARD.with(|heard| heard.set(heard.get() + 1)));
+        block_on(samples.next_sample()).unwrap();
+        block_on(samples.next_sample()).unwrap();
+        assert_eq!(HEARD.with(Cell::get), 2);
+
+        sensor(samples.hts221).fail_from = Some(0);
+        assert!(matches!(block_on(samples.run()), Hts221Error::I2c(_)));
+        assert_eq!(HEARD.with(Cell::get), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "fugit")]
+    fn nominal_interval_follows_output_data_rate() {
+        let mut hts221 = at_7hz();
+        let mut samples = hts221.samples(NoDelay::default());
+        assert_eq!(samples.nominal_interval(), None);
+        block_on(samples.next_sample()).unwrap();
+        assert_eq!(
+            samples.nominal_interval(),
+            Some(fugit::MillisDurationU32::from_ticks(143))
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use super::{
+    Celsius, Config, Hts221, Hts221Error, Interface, OutputDataRate, SampleListener,
+    SensorAcquisition,
+};
+use core::future::Future;
+use embedded_hal_async::delay::DelayUs;
+
+/// Destination of the readings of [`Hts221::run_scheduled`], typically the sending half of
+/// a channel to the task processing them.
+pub trait ReadingSink {
+    /// Pass on a reading, waiting for room if needed.
+    async fn send(&mut self, reading: SensorAcquisition<Celsius>);
+}
+
+impl ReadingSink for SampleListener {
+    async fn send(&mut self, reading: SensorAcquisition<Celsius>) {
+        self(&reading)
+    }
+}
+
+impl<I> Hts221<I>
+where
+    I: Interface,
+{
+    /// Take a one-shot measurement every time a wake-up future resolves, passing the readings
+    /// to `sink`, until an error occurs.
+    ///
+    /// `wakeup` creates the future to wait for before each measurement, typically an RTC
+    /// alarm. The sensor is switched to one-shot mode, so it idles in low power between
+    /// measurements, and `delay` is used to poll for the end of each acquisition.
+    pub async fn run_scheduled<W, F, S, D>(
+        &mut self,
+        mut wakeup: W,
+        sink: &mut S,
+        delay: &mut D,
+    ) -> Hts221Error<I::Error>
+    where
+        W: FnMut() -> F,
+        F: Future<Output = ()>,
+        S: ReadingSink,
+        D: DelayUs,
+    {
+        if self.config.output_data_rate != OutputDataRate::OneShot {
+            let config = Config {
+                output_data_rate: OutputDataRate::OneShot,
+                ..self.config
+            };
+            if let Err(e) = self.reconfigure(config).await {
+                return e;
+            }
+        }
+        loop {
+            wakeup().await;
+            match self.wait_for_sample(OutputDataRate::OneShot, delay).await {
+                Ok(reading) => sink.send(reading).await,
+                Err(e) => return e,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[derive(Default)]
+    struct Readings(std::vec::Vec<SensorAcquisition<Celsius>>);
+
+    impl ReadingSink for Readings {
+        async fn send(&mut self, reading: SensorAcquisition<Celsius>) {
+            self.0.push(reading);
+        }
+    }
+
+    #[test]
+    fn measures_on_every_wakeup() {
+        let mut hts221 = initialized();
+        let mut readings = Readings::default();
+        let mut delay = NoDelay::default();
+        // Three alarms fire, the fourth never does
+        let mut alarms = 0;
+        let wakeup = move || {
+            alarms += 1;
+            let fired = alarms <= 3;
+            async move {
+                if !fired {
+                    core::future::pending::<()>().await
+                }
+            }
+        };
+
+        let mut run = Box::pin(hts221.run_scheduled(wakeup, &mut readings, &mut delay));
+        assert!(poll_once(&mut run).is_pending());
+        drop(run);
+
+        assert_eq!(readings.0.len(), 3);
+        assert_eq!(hts221.config().output_data_rate, OutputDataRate::OneShot);
+    }
+}
@@ -0,0 +1,273 @@
+#[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+use super::{Celsius, Temperature};
+use super::{Hts221, Hts221Error, Interface};
+use core::{
+    cell::{Cell, UnsafeCell},
+    future::poll_fn,
+    ops::{Deref, DerefMut},
+    task::{Poll, Waker},
+};
+
+/// An HTS221 driver shared between a temperature and a humidity handle.
+///
+/// Access to the underlying driver is serialized, so the handles can be used from
+/// independent tasks running on the same executor.
+pub struct SharedHts221<I>
+where
+    I: Interface,
+{
+    driver: UnsafeCell<Hts221<I>>,
+    locked: Cell<bool>,
+    waiters: WakerQueue,
+}
+
+impl<I> SharedHts221<I>
+where
+    I: Interface,
+{
+    /// Create shared state around an existing driver.
+    pub fn new(driver: Hts221<I>) -> Self {
+        Self {
+            driver: UnsafeCell::new(driver),
+            locked: Cell::new(false),
+            waiters: WakerQueue::new(),
+        }
+    }
+
+    /// Split into a handle for temperature reads and a handle for humidity reads.
+    #[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+    pub fn split(&self) -> (TemperatureHandle<'_, I>, HumidityHandle<'_, I>) {
+        (
+            TemperatureHandle { shared: self },
+            HumidityHandle { shared: self },
+        )
+    }
+
+    /// Initialize the shared driver. Must be run before reading sensor values.
+    pub async fn initialize(&self) -> Result<(), Hts221Error<I::Error>> {
+        self.lock().await.initialize().await
+    }
+
+    /// Release the underlying driver.
+    pub fn into_inner(self) -> Hts221<I> {
+        self.driver.into_inner()
+    }
+
+    async fn lock(&self) -> Guard<'_, I> {
+        poll_fn(|cx| {
+            if self.locked.replace(true) {
+                self.waiters.register(cx.waker());
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+        .await;
+        Guard { shared: self }
+    }
+}
+
+struct Guard<'a, I>
+where
+    I: Interface,
+{
+    shared: &'a SharedHts221<I>,
+}
+
+impl<'a, I> Deref for Guard<'a, I>
+where
+    I: Interface,
+{
+    type Target = Hts221<I>;
+
+    fn deref(&self) -> &Self::Target {
+        // Safety: the lock flag guarantees a single guard exists at a time.
+        unsafe { &*self.shared.driver.get() }
+    }
+}
+
+impl<'a, I> DerefMut for Guard<'a, I>
+where
+    I: Interface,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // Safety: the lock flag guarantees a single guard exists at a time.
+        unsafe { &mut *self.shared.driver.get() }
+    }
+}
+
+impl<'a, I> Drop for Guard<'a, I>
+where
+    I: Interface,
+{
+    fn drop(&mut self) {
+        self.shared.locked.set(false);
+        self.shared.waiters.wake();
+    }
+}
+
+// Tasks waiting for a state change at the same time, beyond which waiting tasks poll
+// continuously
+const WAITERS: usize = 4;
+
+// Wakers of the tasks waiting for a state change, all woken in the order they registered by
+// the task making the change.
+//
+// Waking every waiter means a waiter dropped before it polls again cannot swallow the wakeup
+// of the others, and waiters never wake each other while the state is unchanged.
+pub(crate) struct WakerQueue {
+    wakers: [Cell<Option<Waker>>; WAITERS],
+    len: Cell<usize>,
+}
+
+impl WakerQueue {
+    pub(crate) const fn new() -> Self {
+        // Only used to repeat the initializer, each element is a distinct cell
+        #[allow(clippy::declare_interior_mutable_const)]
+        const EMPTY: Cell<Option<Waker>> = Cell::new(None);
+        Self {
+            wakers: [EMPTY; WAITERS],
+            len: Cell::new(0),
+        }
+    }
+
+    pub(crate) fn register(&self, waker: &Waker) {
+        let len = self.len.get();
+        for slot in &self.wakers[..len] {
+            let registered = slot.take();
+            let found = matches!(&registered, Some(registered) if registered.will_wake(waker));
+            slot.set(registered);
+            if found {
+                return;
+            }
+        }
+        match self.wakers.get(len) {
+            Some(slot) => {
+                slot.set(Some(waker.clone()));
+                self.len.set(len + 1);
+            }
+            // No room left, so poll again rather than miss the change
+            None => waker.wake_by_ref(),
+        }
+    }
+
+    pub(crate) fn wake(&self) {
+        let len = self.len.replace(0);
+        for slot in &self.wakers[..len] {
+            if let Some(waker) = slot.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+#[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+/// Handle exposing only temperature reads of a [`SharedHts221`].
+pub struct TemperatureHandle<'a, I>
+where
+    I: Interface,
+{
+    shared: &'a SharedHts221<I>,
+}
+
+#[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+impl<'a, I> TemperatureHandle<'a, I>
+where
+    I: Interface,
+{
+    /// Read the temperature value.
+    pub async fn read(&self) -> Result<Temperature<Celsius>, Hts221Error<I::Error>> {
+        self.shared.lock().await.read_temperature().await
+    }
+}
+
+#[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+/// Handle exposing only relative humidity reads of a [`SharedHts221`].
+pub struct HumidityHandle<'a, I>
+where
+    I: Interface,
+{
+    shared: &'a SharedHts221<I>,
+}
+
+#[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+impl<'a, I> HumidityHandle<'a, I>
+where
+    I: Interface,
+{
+    /// Read the relative humidity value.
+    pub async fn read(&self) -> Result<f32, Hts221Error<I::Error>> {
+        self.shared.lock().await.read_humidity().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use core::{future::Future, task::Context};
+
+    #[test]
+    fn waiter_is_woken_on_unlock() {
+        let shared = SharedHts221::new(initialized());
+        let guard = block_on(shared.lock());
+        let (waker, wakes) = counting_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut lock = Box::pin(shared.lock());
+        assert!(lock.as_mut().poll(&mut cx).is_pending());
+        assert!(lock.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(wakes(), 0);
+        drop(guard);
+        assert_eq!(wakes(), 1);
+        assert!(lock.as_mut().poll(&mut cx).is_ready());
+    }
+
+    #[test]
+    fn contending_waiters_are_woken_once_per_unlock() {
+        let shared = SharedHts221::new(initialized());
+        let guard = block_on(shared.lock());
+        let waiters: std::vec::Vec<_> = (0..3).map(|_| counting_waker()).collect();
+        let mut locks: std::vec::Vec<_> = (0..3).map(|_| Box::pin(shared.lock())).collect();
+        // Every waiter polls a few times while the lock is held, without waking the others
+        for _ in 0..3 {
+            for (lock, (waker, _)) in locks.iter_mut().zip(&waiters) {
+                let mut cx = Context::from_waker(waker);
+                assert!(lock.as_mut().poll(&mut cx).is_pending());
+            }
+        }
+        assert!(waiters.iter().all(|(_, wakes)| wakes() == 0));
+
+        drop(guard);
+        assert!(waiters.iter().all(|(_, wakes)| wakes() == 1));
+        let mut cx = Context::from_waker(&waiters[0].0);
+        let first = locks[0].as_mut().poll(&mut cx);
+        assert!(first.is_ready());
+        // The others wait for the next unlock
+        for (lock, (waker, _)) in locks.iter_mut().zip(&waiters).skip(1) {
+            let mut cx = Context::from_waker(waker);
+            assert!(lock.as_mut().poll(&mut cx).is_pending());
+        }
+        assert!(waiters.iter().all(|(_, wakes)| wakes() == 1));
+
+        drop(first);
+        assert_eq!(waiters[1].1(), 2);
+        assert_eq!(waiters[2].1(), 2);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "temperature-only", feature = "humidity-only")))]
+    fn handles_check_for_reset() {
+        let mut hts221 = initialized();
+        hts221.set_reset_check_interval(2);
+        let shared = hts221.into_shared();
+        let (temperature, humidity) = shared.split();
+
+        block_on(temperature.read()).unwrap();
+        sensor(&mut block_on(shared.lock())).regs[CTRL_REG1 as usize] = 0;
+        block_on(humidity.read()).unwrap();
+        assert_ne!(
+            sensor(&mut block_on(shared.lock())).regs[CTRL_REG1 as usize],
+            0
+        );
+    }
+}
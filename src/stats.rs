@@ -0,0 +1,76 @@
+use super::{Hts221Error, Interface};
+use embedded_hal_async::i2c::ErrorKind;
+
+/// Counters maintained by the driver, suitable for device health telemetry.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Stats {
+    /// Number of successful sensor reads.
+    pub reads: u32,
+    /// Number of failed I2C transactions.
+    pub i2c_errors: u32,
+    /// Number of I2C transactions retried by the driver.
+    pub retries: u32,
+    /// Number of times the driver was initialized again after a successful initialization.
+    pub reinits: u32,
+    /// Number of spontaneous sensor resets detected and recovered from.
+    pub resets: u32,
+    /// Kind of the most recent I2C error.
+    pub last_error: Option<ErrorKind>,
+}
+
+impl Stats {
+    pub(crate) fn error<I: Interface>(&mut self, error: &Hts221Error<I::Error>) {
+        match error {
+            Hts221Error::I2c(e) => self.i2c::<I>(e),
+            Hts221Error::Timeout => {
+                self.i2c_errors = self.i2c_errors.saturating_add(1);
+                self.last_error.replace(ErrorKind::Other);
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn retry<I: Interface>(&mut self, error: &I::Error) {
+        self.retries = self.retries.saturating_add(1);
+        self.i2c::<I>(error);
+    }
+
+    fn i2c<I: Interface>(&mut self, error: &I::Error) {
+        self.i2c_errors = self.i2c_errors.saturating_add(1);
+        self.last_error.replace(I::error_kind(error));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::*;
+    use embedded_hal_async::i2c::ErrorKind;
+
+    #[test]
+    fn counts_reads_errors_and_reinits() {
+        let mut hts221 = initialized();
+        block_on(hts221.read()).unwrap();
+        block_on(hts221.read()).unwrap();
+        sensor(&mut hts221).fail = 1;
+        sensor(&mut hts221).error = ErrorKind::Bus;
+        assert!(block_on(hts221.read()).is_err());
+        block_on(hts221.initialize()).unwrap();
+
+        let stats = hts221.stats();
+        assert_eq!(stats.reads, 2);
+        assert_eq!(stats.i2c_errors, 1);
+        assert_eq!(stats.last_error, Some(ErrorKind::Bus));
+        assert_eq!(stats.reinits, 1);
+        assert_eq!(stats.retries, 0);
+    }
+
+    #[test]
+    fn counts_retries() {
+        let mut hts221 = initialized();
+        sensor(&mut hts221).fail_register = Some(STATUS);
+        assert!(block_on(hts221.initialize()).is_err());
+        let stats = hts221.stats();
+        assert!(stats.retries > 0);
+        assert_eq!(stats.i2c_errors, stats.retries + 1);
+    }
+}
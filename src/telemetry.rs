@@ -0,0 +1,181 @@
+use super::{Hts221Error, Interface};
+use embedded_hal_async::i2c::ErrorKind;
+
+/// Callback invoked on every driver error, see [`Hts221::set_error_hook`](super::Hts221::set_error_hook).
+pub type ErrorHook = fn(&ErrorEvent);
+
+/// Category of a driver error, independent of the transport error type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Hts221ErrorKind {
+    /// Error from the bus, classified by the interface.
+    Bus(ErrorKind),
+    /// A bus operation did not complete in time.
+    Timeout,
+    /// Attempting to read before calibration.
+    NotCalibrated,
+    /// Not the expected sensor device.
+    InvalidSensor,
+    /// The calibration data stored on the sensor is not usable.
+    BadCalibration,
+    /// A configuration register did not read back the value written to it.
+    ConfigVerifyFailed,
+    /// The values would be affected by the heater.
+    HeaterActive,
+    /// The operation needs a compiled-out channel.
+    ChannelUnavailable,
+    /// The sensor was reset underneath the driver and has been configured again.
+    DeviceReset,
+}
+
+/// Error reported to an [`ErrorHook`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ErrorEvent {
+    /// Category of the error.
+    pub kind: Hts221ErrorKind,
+    /// Register accessed by the failing bus operation, if the error came from the bus.
+    pub register: Option<u8>,
+}
+
+impl ErrorEvent {
+    pub(crate) fn new<I: Interface>(error: &Hts221Error<I::Error>, register: u8) -> Self {
+        let (kind, register) = match error {
+            Hts221Error::I2c(e) => (Hts221ErrorKind::Bus(I::error_kind(e)), Some(register)),
+            Hts221Error::Timeout => (Hts221ErrorKind::Timeout, Some(register)),
+            Hts221Error::NotCalibrated => (Hts221ErrorKind::NotCalibrated, None),
+            Hts221Error::InvalidSensor { .. } => (Hts221ErrorKind::InvalidSensor, None),
+            Hts221Error::BadCalibration => (Hts221ErrorKind::BadCalibration, None),
+            Hts221Error::ConfigVerifyFailed { register, .. } => {
+                (Hts221ErrorKind::ConfigVerifyFailed, Some(*register))
+            }
+            Hts221Error::HeaterActive => (Hts221ErrorKind::HeaterActive, None),
+            Hts221Error::ChannelUnavailable => (Hts221ErrorKind::ChannelUnavailable, None),
+        };
+        Self { kind, register }
+    }
+}
+
+// Interface wrapper remembering the last register accessed, giving errors their context.
+pub(crate) struct Tracked<I> {
+    pub(crate) interface: I,
+    pub(crate) register: u8,
+}
+
+impl<I: Interface> Tracked<I> {
+    pub(crate) fn new(interface: I) -> Self {
+        Self {
+            interface,
+            register: 0,
+        }
+    }
+}
+
+impl<I: Interface> Interface for Tracked<I> {
+    type Error = I::Error;
+
+    async fn read_registers(&mut self, start: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.register = start;
+        self.interface.read_registers(start, buf).await
+    }
+
+    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), Self::Error> {
+        self.register = register;
+        self.interface.write_register(register, value).await
+    }
+
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        I::error_kind(error)
+    }
+
+    fn is_timeout(error: &Self::Error) -> bool {
+        I::is_timeout(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use crate::{Hts221, OutputDataRate};
+    use core::cell::RefCell;
+
+    std::thread_local! {
+        static EVENTS: RefCell<std::vec::Vec<ErrorEvent>> =
+            const { RefCell::new(std::vec::Vec::new()) };
+    }
+
+    fn record(event: &ErrorEvent) {
+        EVENTS.with(|events| events.borrow_mut().push(*event));
+    }
+
+    fn events() -> std::vec::Vec<ErrorEvent> {
+        EVENTS.with(|events| events.borrow_mut().drain(..).collect())
+    }
+
+    fn hooked(mut hts221: Hts221<FakeSensor>) -> Hts221<FakeSensor> {
+        hts221.set_error_hook(record);
+        events();
+        hts221
+    }
+
+    #[test]
+    fn reports_bus_error_with_register() {
+        let mut hts221 = hooked(initialized());
+        sensor(&mut hts221).fail_register = Some(CTRL_REG1);
+        sensor(&mut hts221).error = ErrorKind::ArbitrationLoss;
+        let mut config = hts221.config();
+        config.output_data_rate = OutputDataRate::Hz7;
+        assert!(block_on(hts221.reconfigure(config)).is_err());
+        assert_eq!(
+            events(),
+            [ErrorEvent {
+                kind: Hts221ErrorKind::Bus(ErrorKind::ArbitrationLoss),
+                register: Some(CTRL_REG1),
+            }]
+        );
+    }
+
+    #[test]
+    fn reports_driver_errors_without_register() {
+        let mut uninitialized = hooked(Hts221::with_interface(FakeSensor::new()));
+        assert!(block_on(uninitialized.read()).is_err());
+        sensor(&mut uninitialized).regs[WHO_AM_I as usize] = 0;
+        assert!(block_on(uninitialized.initialize()).is_err());
+        assert_eq!(
+            events(),
+            [
+                ErrorEvent {
+                    kind: Hts221ErrorKind::NotCalibrated,
+                    register: None,
+                },
+                ErrorEvent {
+                    kind: Hts221ErrorKind::InvalidSensor,
+                    register: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_verify_failure_with_register() {
+        let mut hts221 = hooked(initialized());
+        hts221.set_verify_config(true);
+        sensor(&mut hts221).stuck_bits = Some((CTRL_REG3, 0b1000_0000));
+        let mut config = hts221.config();
+        config.data_ready.enabled = false;
+        assert!(block_on(hts221.reconfigure(config)).is_err());
+        assert_eq!(
+            events(),
+            [ErrorEvent {
+                kind: Hts221ErrorKind::ConfigVerifyFailed,
+                register: Some(CTRL_REG3),
+            }]
+        );
+    }
+
+    #[test]
+    fn silent_on_success() {
+        let mut hts221 = hooked(initialized());
+        block_on(hts221.read()).unwrap();
+        assert!(events().is_empty());
+    }
+}
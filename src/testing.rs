@@ -0,0 +1,289 @@
+//! Test doubles shared by the unit tests: a simulated sensor behind the [`Interface`] and
+//! I2C traits, a delay which does not wait and a minimal executor.
+
+pub(crate) use super::executor::{block_on, noop_waker};
+use super::{Hts221, Interface};
+use core::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll, Waker},
+};
+use embedded_hal_async::{delay::DelayUs, i2c::*};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::Wake,
+};
+
+pub const WHO_AM_I: u8 = 0x0F;
+pub const AV_CONF: u8 = 0x10;
+pub const CTRL_REG1: u8 = 0x20;
+pub const CTRL_REG2: u8 = 0x21;
+pub const CTRL_REG3: u8 = 0x22;
+pub const STATUS: u8 = 0x27;
+pub const H_OUT: u8 = 0x28;
+pub const T_OUT: u8 = 0x2A;
+pub const CALIBRATION: u8 = 0x30;
+
+const ADDRESS: u8 = 0x5F;
+const T_DA: u8 = 0b01;
+const H_DA: u8 = 0b10;
+const ONE_SHOT: u8 = 0b001;
+const HEATER: u8 = 0b010;
+const BOOT: u8 = 0b1000_0000;
+
+// 20 to 30 °C over raw 0 to 1000, 40 to 70 %rH over raw 0 to 6000
+pub const CALIBRATION_BLOCK: [u8; 16] = [
+    80, 140, 160, 240, 0, 0, 0, 0, 0, 0, 0x70, 0x17, 0, 0, 0xe8, 0x03,
+];
+
+/// Error of the simulated sensor.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct FakeError(pub ErrorKind);
+
+impl Error for FakeError {
+    fn kind(&self) -> ErrorKind {
+        self.0
+    }
+}
+
+/// Simulated HTS221 register map.
+///
+/// Samples set with [`sample`](Self::sample) raise the status bits, which reading the high
+/// output bytes clears again. A one-shot request produces a sample after
+/// `one_shot_polls` reads of CTRL_REG2. In continuous mode, every `sample_every` reads of
+/// the status register produce a sample.
+pub struct FakeSensor {
+    pub regs: [u8; 0x40],
+    pub writes: std::vec::Vec<(u8, u8)>,
+    pub reads: usize,
+    pub one_shot_polls: usize,
+    pub sample_every: Option<usize>,
+    pub next_sample: Option<(f32, f32)>,
+    /// Number of upcoming accesses failing with `error`.
+    pub fail: usize,
+    /// Index of the access from which on all accesses fail with `error`.
+    pub fail_from: Option<usize>,
+    /// Register whose accesses always fail with `error`.
+    pub fail_register: Option<u8>,
+    pub error: ErrorKind,
+    /// Bits of a register which always read as set.
+    pub stuck_bits: Option<(u8, u8)>,
+    accesses: usize,
+    status_reads: usize,
+    pending_polls: usize,
+}
+
+impl FakeSensor {
+    pub fn new() -> Self {
+        let mut regs = [0; 0x40];
+        regs[WHO_AM_I as usize] = 0xBC;
+        regs[CALIBRATION as usize..].copy_from_slice(&CALIBRATION_BLOCK);
+        Self {
+            regs,
+            writes: std::vec::Vec::new(),
+            reads: 0,
+            one_shot_polls: 1,
+            sample_every: Some(3),
+            next_sample: None,
+            fail: 0,
+            fail_from: None,
+            fail_register: None,
+            error: ErrorKind::Bus,
+            stuck_bits: None,
+            accesses: 0,
+            status_reads: 0,
+            pending_polls: 0,
+        }
+    }
+
+    /// Latch a new sample into the output registers.
+    pub fn sample(&mut self, celsius: f32, relative_humidity: f32) {
+        let t_out = ((celsius - 20.0) * 100.0) as i16;
+        let h_out = ((relative_humidity - 40.0) * 200.0) as i16;
+        self.regs[T_OUT as usize..T_OUT as usize + 2].copy_from_slice(&t_out.to_le_bytes());
+        self.regs[H_OUT as usize..H_OUT as usize + 2].copy_from_slice(&h_out.to_le_bytes());
+        self.regs[STATUS as usize] |= T_DA | H_DA;
+    }
+
+    pub fn heater_on(&self) -> bool {
+        self.regs[CTRL_REG2 as usize] & HEATER != 0
+    }
+
+    fn check(&mut self, register: u8) -> Result<(), FakeError> {
+        self.accesses += 1;
+        if matches!(self.fail_from, Some(from) if self.accesses > from) {
+            return Err(FakeError(self.error));
+        }
+        if self.fail > 0 {
+            self.fail -= 1;
+            return Err(FakeError(self.error));
+        }
+        if self.fail_register == Some(register) {
+            return Err(FakeError(self.error));
+        }
+        Ok(())
+    }
+}
+
+impl Interface for FakeSensor {
+    type Error = FakeError;
+
+    async fn read_registers(&mut self, start: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        self.check(start)?;
+        self.reads += 1;
+        // A one-shot acquisition completes after polling either its trigger or the status
+        if (start == CTRL_REG2 || start == STATUS) && self.pending_polls > 0 {
+            self.pending_polls -= 1;
+            if self.pending_polls == 0 {
+                self.regs[CTRL_REG2 as usize] &= !ONE_SHOT;
+                let (celsius, relative_humidity) = self.next_sample.unwrap_or((25.0, 50.0));
+                self.sample(celsius, relative_humidity);
+            }
+        }
+        let ctrl1 = self.regs[CTRL_REG1 as usize];
+        if start == STATUS && ctrl1 & 0x80 != 0 && ctrl1 & 0b11 != 0 {
+            self.status_reads += 1;
+            if matches!(self.sample_every, Some(every) if self.status_reads >= every) {
+                self.status_reads = 0;
+                let (celsius, relative_humidity) = self.next_sample.unwrap_or((25.0, 50.0));
+                self.sample(celsius, relative_humidity);
+            }
+        }
+        for (i, byte) in buf.iter_mut().enumerate() {
+            let register = start as usize + i;
+            *byte = self.regs[register];
+            if let Some((stuck, bits)) = self.stuck_bits {
+                if register == stuck as usize {
+                    *byte |= bits;
+                }
+            }
+            if register == T_OUT as usize + 1 {
+                self.regs[STATUS as usize] &= !T_DA;
+            } else if register == H_OUT as usize + 1 {
+                self.regs[STATUS as usize] &= !H_DA;
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), Self::Error> {
+        self.check(register)?;
+        self.writes.push((register, value));
+        self.regs[register as usize] = value;
+        if register == CTRL_REG2 {
+            self.regs[CTRL_REG2 as usize] &= !BOOT;
+            if value & ONE_SHOT != 0 {
+                self.pending_polls = self.one_shot_polls.max(1);
+            }
+        }
+        Ok(())
+    }
+
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        error.0
+    }
+}
+
+impl ErrorType for FakeSensor {
+    type Error = FakeError;
+}
+
+// I2C access to the registers, as done by `I2cInterface`
+impl I2c<SevenBitAddress> for FakeSensor {
+    async fn read(&mut self, _address: u8, _read: &mut [u8]) -> Result<(), Self::Error> {
+        Err(FakeError(ErrorKind::Other))
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        match write {
+            [register, value] if address == ADDRESS => self.write_register(*register, *value).await,
+            _ => Err(FakeError(ErrorKind::Other)),
+        }
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match write {
+            [register] if address == ADDRESS => self.read_registers(register & 0x7F, read).await,
+            _ => Err(FakeError(ErrorKind::Other)),
+        }
+    }
+
+    async fn transaction(
+        &mut self,
+        _address: u8,
+        _operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        Err(FakeError(ErrorKind::Other))
+    }
+}
+
+/// Delay returning immediately, adding up the time it was asked to wait.
+#[derive(Default)]
+pub struct NoDelay {
+    pub total_us: u64,
+}
+
+impl DelayUs for NoDelay {
+    async fn delay_us(&mut self, us: u32) {
+        self.total_us += us as u64;
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.total_us += ms as u64 * 1000;
+    }
+}
+
+/// Delay which never completes.
+pub struct Stall;
+
+impl DelayUs for Stall {
+    async fn delay_us(&mut self, _us: u32) {
+        core::future::pending().await
+    }
+
+    async fn delay_ms(&mut self, _ms: u32) {
+        core::future::pending().await
+    }
+}
+
+/// A driver initialized against a simulated sensor.
+pub fn initialized() -> Hts221<FakeSensor> {
+    let mut hts221 = Hts221::with_interface(FakeSensor::new());
+    block_on(hts221.initialize()).unwrap();
+    hts221
+}
+
+/// The simulated sensor behind a driver.
+pub fn sensor<I: Interface>(hts221: &mut Hts221<I>) -> &mut I {
+    &mut hts221.interface.interface
+}
+
+/// A waker counting how often it was woken, and a function returning the count.
+pub fn counting_waker() -> (Waker, impl Fn() -> usize) {
+    struct Counter(AtomicUsize);
+
+    impl Wake for Counter {
+        fn wake(self: Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let counter = Arc::new(Counter(AtomicUsize::new(0)));
+    let count = counter.clone();
+    (Waker::from(counter), move || count.0.load(Ordering::SeqCst))
+}
+
+/// Poll a future once.
+pub fn poll_once<F: Future + Unpin>(future: &mut F) -> Poll<F::Output> {
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    Pin::new(future).poll(&mut cx)
+}
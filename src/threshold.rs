@@ -0,0 +1,330 @@
+use super::{
+    ActiveState, Celsius, Config, DataReadyConfig, Hts221, Hts221Error, Interface, OutputDataRate,
+    SensorAcquisition, HAS_HUMIDITY, HAS_TEMPERATURE,
+};
+use embedded_hal_async::{delay::DelayUs, digital::Wait};
+
+/// Condition on sensor values waited for by [`Hts221::wait_for_threshold`].
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Threshold {
+    /// Temperature in celsius rises above the value.
+    TemperatureAbove(f32),
+    /// Temperature in celsius falls below the value.
+    TemperatureBelow(f32),
+    /// Relative humidity rises above the value.
+    HumidityAbove(f32),
+    /// Relative humidity falls below the value.
+    HumidityBelow(f32),
+}
+
+impl Threshold {
+    /// Whether an acquisition crosses the threshold.
+    pub fn crossed(&self, acquisition: &SensorAcquisition<Celsius>) -> bool {
+        match *self {
+            Threshold::TemperatureAbove(t) => acquisition.temperature.value > t,
+            Threshold::TemperatureBelow(t) => acquisition.temperature.value < t,
+            Threshold::HumidityAbove(rh) => acquisition.relative_humidity > rh,
+            Threshold::HumidityBelow(rh) => acquisition.relative_humidity < rh,
+        }
+    }
+
+    // Whether the channel compared is compiled in, as it never crosses otherwise
+    fn available(&self) -> bool {
+        match self {
+            Threshold::TemperatureAbove(_) | Threshold::TemperatureBelow(_) => HAS_TEMPERATURE,
+            Threshold::HumidityAbove(_) | Threshold::HumidityBelow(_) => HAS_HUMIDITY,
+        }
+    }
+}
+
+/// Error returned by [`Hts221::wait_for_threshold_drdy`].
+#[derive(Debug)]
+pub enum WakeError<E, P> {
+    /// Error from the driver.
+    Driver(Hts221Error<E>),
+    /// Error waiting for the data ready pin.
+    Pin(P),
+}
+
+impl<E, P> From<Hts221Error<E>> for WakeError<E, P> {
+    fn from(e: Hts221Error<E>) -> Self {
+        WakeError::Driver(e)
+    }
+}
+
+impl<I> Hts221<I>
+where
+    I: Interface,
+{
+    /// Wait until the sensor values cross a threshold, returning the acquisition which
+    /// crossed it.
+    ///
+    /// The HTS221 has no threshold interrupt, so this emulates one: the sensor is switched to
+    /// the lowest output data rate and every sample is compared, using `delay` to sleep
+    /// between samples. The configuration is restored once the wait completes or fails.
+    ///
+    /// A threshold on a channel compiled out with the `temperature-only` or `humidity-only`
+    /// feature fails with [`Hts221Error::ChannelUnavailable`].
+    pub async fn wait_for_threshold<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+        threshold: Threshold,
+    ) -> Result<SensorAcquisition<Celsius>, Hts221Error<I::Error>> {
+        if !threshold.available() {
+            return Err(Hts221Error::ChannelUnavailable);
+        }
+        let saved = self.config();
+        let result = self.wait_at_lowest_odr(delay, threshold).await;
+        let restored = self.reconfigure(saved).await;
+        let acquisition = result?;
+        restored?;
+        Ok(acquisition)
+    }
+
+    async fn wait_at_lowest_odr<D: DelayUs>(
+        &mut self,
+        delay: &mut D,
+        threshold: Threshold,
+    ) -> Result<SensorAcquisition<Celsius>, Hts221Error<I::Error>> {
+        let config = Config {
+            output_data_rate: OutputDataRate::Hz1,
+            ..self.config()
+        };
+        self.reconfigure(config).await?;
+        loop {
+            let acquisition = self.wait_for_sample(OutputDataRate::Hz1, delay).await?;
+            if threshold.crossed(&acquisition) {
+                return Ok(acquisition);
+            }
+        }
+    }
+
+    /// Wait until the sensor values cross a threshold like
+    /// [`wait_for_threshold`](Self::wait_for_threshold), sleeping on the data ready pin
+    /// between samples instead of polling.
+    ///
+    /// The data ready signal is enabled for the wait if it is disabled in the configuration.
+    /// The configuration is restored once the wait completes or fails.
+    pub async fn wait_for_threshold_drdy<P: Wait>(
+        &mut self,
+        drdy: &mut P,
+        threshold: Threshold,
+    ) -> Result<SensorAcquisition<Celsius>, WakeError<I::Error, P::Error>> {
+        if !threshold.available() {
+            return Err(Hts221Error::ChannelUnavailable.into());
+        }
+        let saved = self.config();
+        let config = Config {
+            output_data_rate: OutputDataRate::Hz1,
+            data_ready: DataReadyConfig {
+                enabled: true,
+                ..saved.data_ready
+            },
+            ..saved
+        };
+        let result = match self.reconfigure(config).await {
+            Ok(()) => {
+                self.wait_for_drdy(drdy, threshold, saved.data_ready.active_state)
+                    .await
+            }
+            Err(e) => Err(e.into()),
+        };
+        let restored = self.reconfigure(saved).await;
+        let acquisition = result?;
+        restored?;
+        Ok(acquisition)
+    }
+
+    async fn wait_for_drdy<P: Wait>(
+        &mut self,
+        drdy: &mut P,
+        threshold: Threshold,
+        active_state: ActiveState,
+    ) -> Result<SensorAcquisition<Celsius>, WakeError<I::Error, P::Error>> {
+        loop {
+            match active_state {
+                ActiveState::High => drdy.wait_for_high().await,
+                ActiveState::Low => drdy.wait_for_low().await,
+            }
+            .map_err(WakeError::Pin)?;
+            // Reading the output registers clears the data ready signal
+            let acquisition = self.read().await?;
+            if threshold.crossed(&acquisition) {
+                return Ok(acquisition);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+    use embedded_hal::digital::{ErrorKind, ErrorType};
+
+    #[derive(Debug)]
+    struct PinError;
+
+    impl embedded_hal::digital::Error for PinError {
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    // Data ready pin which is always active, or always fails
+    struct Pin {
+        fail: bool,
+    }
+
+    impl Pin {
+        fn level(&self) -> Result<(), PinError> {
+            if self.fail {
+                Err(PinError)
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl ErrorType for Pin {
+        type Error = PinError;
+    }
+
+    impl Wait for Pin {
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            self.level()
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            self.level()
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            self.level()
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            self.level()
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            self.level()
+        }
+    }
+
+    const DRDY_EN: u8 = 0b100;
+    const ODR: u8 = 0b11;
+    const ODR_7HZ: u8 = 0b10;
+
+    // Threshold on a compiled-in channel, crossed by any sample
+    fn always() -> Threshold {
+        if HAS_HUMIDITY {
+            Threshold::HumidityAbove(-1.0)
+        } else {
+            Threshold::TemperatureAbove(-100.0)
+        }
+    }
+
+    fn at_7hz() -> Hts221<FakeSensor> {
+        let mut hts221 = initialized();
+        let config = Config {
+            output_data_rate: OutputDataRate::Hz7,
+            ..hts221.config()
+        };
+        block_on(hts221.reconfigure(config)).unwrap();
+        sensor(&mut hts221).writes.clear();
+        hts221
+    }
+
+    fn odr(hts221: &mut Hts221<FakeSensor>) -> u8 {
+        sensor(hts221).regs[CTRL_REG1 as usize] & ODR
+    }
+
+    #[test]
+    fn restores_output_data_rate() {
+        let mut hts221 = at_7hz();
+        block_on(hts221.wait_for_threshold(&mut NoDelay::default(), always())).unwrap();
+        assert_eq!(odr(&mut hts221), ODR_7HZ);
+        assert_eq!(hts221.config().output_data_rate, OutputDataRate::Hz7);
+    }
+
+    #[test]
+    fn restores_output_data_rate_on_error() {
+        let mut hts221 = at_7hz();
+        sensor(&mut hts221).fail_register = Some(STATUS);
+        assert!(matches!(
+            block_on(hts221.wait_for_threshold(&mut NoDelay::default(), always())),
+            Err(Hts221Error::I2c(_))
+        ));
+        assert_eq!(odr(&mut hts221), ODR_7HZ);
+        assert_eq!(hts221.config().output_data_rate, OutputDataRate::Hz7);
+    }
+
+    #[test]
+    fn restores_configuration_on_pin_error() {
+        let mut hts221 = at_7hz();
+        let mut config = hts221.config();
+        config.data_ready.enabled = false;
+        block_on(hts221.reconfigure(config)).unwrap();
+
+        assert!(matches!(
+            block_on(hts221.wait_for_threshold_drdy(&mut Pin { fail: true }, always())),
+            Err(WakeError::Pin(PinError))
+        ));
+        assert_eq!(odr(&mut hts221), ODR_7HZ);
+        assert_eq!(sensor(&mut hts221).regs[CTRL_REG3 as usize] & DRDY_EN, 0);
+        assert_eq!(hts221.config().output_data_rate, OutputDataRate::Hz7);
+        assert!(!hts221.config().data_ready.enabled);
+    }
+
+    #[test]
+    fn enables_data_ready_for_the_wait() {
+        let mut hts221 = initialized();
+        let mut config = hts221.config();
+        config.data_ready.enabled = false;
+        block_on(hts221.reconfigure(config)).unwrap();
+        sensor(&mut hts221).writes.clear();
+
+        block_on(hts221.wait_for_threshold_drdy(&mut Pin { fail: false }, always())).unwrap();
+        let ctrl3: std::vec::Vec<u8> = sensor(&mut hts221)
+            .writes
+            .iter()
+            .filter(|(register, _)| *register == CTRL_REG3)
+            .map(|(_, value)| *value & DRDY_EN)
+            .collect();
+        assert_eq!(ctrl3, [DRDY_EN, 0]);
+        assert!(!hts221.config().data_ready.enabled);
+    }
+
+    #[test]
+    fn keeps_enabled_data_ready() {
+        let mut hts221 = initialized();
+        block_on(hts221.wait_for_threshold_drdy(&mut Pin { fail: false }, always())).unwrap();
+        assert!(hts221.config().data_ready.enabled);
+        assert_eq!(
+            sensor(&mut hts221).regs[CTRL_REG3 as usize] & DRDY_EN,
+            DRDY_EN
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "temperature-only", feature = "humidity-only"))]
+    fn rejects_compiled_out_channel() {
+        let mut hts221 = at_7hz();
+        let threshold = if HAS_HUMIDITY {
+            Threshold::TemperatureAbove(30.0)
+        } else {
+            Threshold::HumidityAbove(60.0)
+        };
+        assert!(matches!(
+            block_on(hts221.wait_for_threshold(&mut NoDelay::default(), threshold)),
+            Err(Hts221Error::ChannelUnavailable)
+        ));
+        assert!(matches!(
+            block_on(hts221.wait_for_threshold_drdy(&mut Pin { fail: false }, threshold)),
+            Err(WakeError::Driver(Hts221Error::ChannelUnavailable))
+        ));
+        assert!(sensor(&mut hts221).writes.is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use super::Interface;
+use core::{future::poll_fn, future::Future, pin::Pin, task::Poll};
+use embedded_hal_async::{
+    delay::DelayUs,
+    i2c::{Error, ErrorKind},
+};
+
+/// Error of an interface wrapped in a [`TimeoutInterface`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TimeoutError<E> {
+    /// The operation did not complete in time.
+    Timeout,
+    /// Error from the wrapped interface.
+    Transport(E),
+}
+
+impl<E: Error> Error for TimeoutError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            TimeoutError::Timeout => ErrorKind::Other,
+            TimeoutError::Transport(e) => e.kind(),
+        }
+    }
+}
+
+/// Wrapper around an interface bounding each operation on the bus.
+///
+/// Every register access is raced against a delay of `timeout_ms`. When the delay
+/// finishes first, the operation is dropped and the driver returns
+/// [`Hts221Error::Timeout`](super::Hts221Error::Timeout), so a stuck bus (for example
+/// SCL held low) cannot hang the calling task forever.
+pub struct TimeoutInterface<I, D> {
+    interface: I,
+    delay: D,
+    timeout_ms: u32,
+}
+
+impl<I, D> TimeoutInterface<I, D>
+where
+    I: Interface,
+    D: DelayUs,
+{
+    /// Wrap an interface, using `delay` to bound each operation to `timeout_ms`.
+    pub fn new(interface: I, delay: D, timeout_ms: u32) -> Self {
+        Self {
+            interface,
+            delay,
+            timeout_ms,
+        }
+    }
+
+    /// Release the wrapped interface and the delay.
+    pub fn release(self) -> (I, D) {
+        (self.interface, self.delay)
+    }
+}
+
+impl<I, D> Interface for TimeoutInterface<I, D>
+where
+    I: Interface,
+    D: DelayUs,
+{
+    type Error = TimeoutError<I::Error>;
+
+    async fn read_registers(&mut self, start: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let operation = self.interface.read_registers(start, buf);
+        match race(operation, self.delay.delay_ms(self.timeout_ms)).await {
+            Some(result) => result.map_err(TimeoutError::Transport),
+            None => Err(TimeoutError::Timeout),
+        }
+    }
+
+    async fn write_register(&mut self, register: u8, value: u8) -> Result<(), Self::Error> {
+        let operation = self.interface.write_register(register, value);
+        match race(operation, self.delay.delay_ms(self.timeout_ms)).await {
+            Some(result) => result.map_err(TimeoutError::Transport),
+            None => Err(TimeoutError::Timeout),
+        }
+    }
+
+    fn error_kind(error: &Self::Error) -> ErrorKind {
+        match error {
+            TimeoutError::Timeout => ErrorKind::Other,
+            TimeoutError::Transport(e) => I::error_kind(e),
+        }
+    }
+
+    fn is_timeout(error: &Self::Error) -> bool {
+        match error {
+            TimeoutError::Timeout => true,
+            TimeoutError::Transport(e) => I::is_timeout(e),
+        }
+    }
+}
+
+// Poll `operation` until it completes, or return `None` once `timeout` completes first.
+async fn race<F: Future, T: Future>(mut operation: F, mut timeout: T) -> Option<F::Output> {
+    // Safety: the futures are shadowed and never moved again.
+    let mut operation = unsafe { Pin::new_unchecked(&mut operation) };
+    let mut timeout = unsafe { Pin::new_unchecked(&mut timeout) };
+    poll_fn(|cx| {
+        if let Poll::Ready(output) = operation.as_mut().poll(cx) {
+            Poll::Ready(Some(output))
+        } else if timeout.as_mut().poll(cx).is_ready() {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{testing::*, Hts221, Hts221Error};
+
+    #[test]
+    fn forwards_error_kind() {
+        let error = TimeoutError::Transport(FakeError(ErrorKind::ArbitrationLoss));
+        assert_eq!(error.kind(), ErrorKind::ArbitrationLoss);
+        assert_eq!(TimeoutError::<FakeError>::Timeout.kind(), ErrorKind::Other);
+    }
+
+    // Interface whose operations never complete, as on a stuck bus
+    struct Stuck;
+
+    impl Interface for Stuck {
+        type Error = FakeError;
+
+        async fn read_registers(&mut self, _start: u8, _buf: &mut [u8]) -> Result<(), FakeError> {
+            core::future::pending().await
+        }
+
+        async fn write_register(&mut self, _register: u8, _value: u8) -> Result<(), FakeError> {
+            core::future::pending().await
+        }
+    }
+
+    #[test]
+    fn times_out_stuck_operation() {
+        let interface = TimeoutInterface::new(Stuck, NoDelay::default(), 10);
+        let mut hts221 = Hts221::with_interface(interface);
+        assert!(matches!(
+            block_on(hts221.health()),
+            Err(Hts221Error::Timeout)
+        ));
+        let (_, delay) = hts221.release().release();
+        assert_eq!(delay.total_us, 10_000);
+    }
+
+    #[test]
+    fn passes_operations_through() {
+        let interface = TimeoutInterface::new(FakeSensor::new(), NoDelay::default(), 10);
+        let mut hts221 = Hts221::with_interface(interface);
+        block_on(hts221.initialize()).unwrap();
+        assert!(block_on(hts221.read()).is_ok());
+
+        let (mut sensor, _) = hts221.release().release();
+        sensor.fail = 1;
+        let mut hts221 =
+            Hts221::with_interface(TimeoutInterface::new(sensor, NoDelay::default(), 10));
+        assert!(matches!(
+            block_on(hts221.health()),
+            Err(Hts221Error::I2c(TimeoutError::Transport(FakeError(
+                ErrorKind::Bus
+            ))))
+        ));
+    }
+}
@@ -0,0 +1,375 @@
+use embedded_hal_async::i2c::*;
+
+const TAG_WRITE: u8 = 0x01;
+const TAG_READ: u8 = 0x02;
+const TAG_WRITE_READ: u8 = 0x03;
+const TAG_ERROR: u8 = 0xFF;
+
+/// Wrapper around an I2C peripheral recording all register traffic into a trace.
+///
+/// The trace is a compact byte encoding which can be played back with a [`Replayer`].
+/// Each entry is a tag byte, the device address, and length-prefixed payloads. A failed
+/// transaction is recorded as an error entry holding the kind of error, so replaying a trace
+/// reproduces the failure.
+pub struct Recorder<'b, I> {
+    i2c: I,
+    trace: &'b mut [u8],
+    len: usize,
+    overflowed: bool,
+}
+
+impl<'b, I> Recorder<'b, I>
+where
+    I: I2c<SevenBitAddress>,
+{
+    /// Create a recorder around an I2C peripheral, storing the trace in the given buffer.
+    pub fn new(i2c: I, trace: &'b mut [u8]) -> Self {
+        Self {
+            i2c,
+            trace,
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// The trace recorded so far.
+    pub fn trace(&self) -> &[u8] {
+        &self.trace[..self.len]
+    }
+
+    /// Whether the trace buffer ran out of space. Traffic after that point is not recorded.
+    pub fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Release the I2C peripheral and the recorded trace.
+    pub fn release(self) -> (I, &'b [u8]) {
+        let trace = &self.trace[..self.len];
+        (self.i2c, trace)
+    }
+
+    fn record(&mut self, tag: u8, address: u8, payloads: &[&[u8]]) {
+        if self.overflowed {
+            return;
+        }
+        let needed = 2 + payloads.iter().map(|p| 1 + p.len()).sum::<usize>();
+        if self.len + needed > self.trace.len() || payloads.iter().any(|p| p.len() > 0xFF) {
+            self.overflowed = true;
+            return;
+        }
+        self.push(tag);
+        self.push(address);
+        for payload in payloads {
+            self.push(payload.len() as u8);
+            self.trace[self.len..self.len + payload.len()].copy_from_slice(payload);
+            self.len += payload.len();
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.trace[self.len] = byte;
+        self.len += 1;
+    }
+}
+
+impl<'b, I> ErrorType for Recorder<'b, I>
+where
+    I: I2c<SevenBitAddress>,
+{
+    type Error = I::Error;
+}
+
+impl<'b, I> I2c<SevenBitAddress> for Recorder<'b, I>
+where
+    I: I2c<SevenBitAddress>,
+{
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        match self.i2c.read(address, read).await {
+            Ok(()) => {
+                self.record(TAG_READ, address, &[read]);
+                Ok(())
+            }
+            Err(e) => {
+                self.record(TAG_ERROR, address, &[&[encode_kind(e.kind())]]);
+                Err(e)
+            }
+        }
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        match self.i2c.write(address, write).await {
+            Ok(()) => {
+                self.record(TAG_WRITE, address, &[write]);
+                Ok(())
+            }
+            Err(e) => {
+                self.record(TAG_ERROR, address, &[&[encode_kind(e.kind())]]);
+                Err(e)
+            }
+        }
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        match self.i2c.write_read(address, write, read).await {
+            Ok(()) => {
+                self.record(TAG_WRITE_READ, address, &[write, read]);
+                Ok(())
+            }
+            Err(e) => {
+                self.record(TAG_ERROR, address, &[&[encode_kind(e.kind())]]);
+                Err(e)
+            }
+        }
+    }
+
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        match self.i2c.transaction(address, operations).await {
+            Ok(()) => {
+                for operation in operations.iter() {
+                    match operation {
+                        Operation::Read(read) => self.record(TAG_READ, address, &[read]),
+                        Operation::Write(write) => self.record(TAG_WRITE, address, &[write]),
+                    }
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.record(TAG_ERROR, address, &[&[encode_kind(e.kind())]]);
+                Err(e)
+            }
+        }
+    }
+}
+
+// Error kinds are recorded as a single byte, unknown kinds as `Other`
+fn encode_kind(kind: ErrorKind) -> u8 {
+    match kind {
+        ErrorKind::Bus => 1,
+        ErrorKind::ArbitrationLoss => 2,
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address) => 3,
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data) => 4,
+        ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown) => 5,
+        ErrorKind::Overrun => 6,
+        _ => 0,
+    }
+}
+
+fn decode_kind(byte: u8) -> ErrorKind {
+    match byte {
+        1 => ErrorKind::Bus,
+        2 => ErrorKind::ArbitrationLoss,
+        3 => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+        4 => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data),
+        5 => ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+        6 => ErrorKind::Overrun,
+        _ => ErrorKind::Other,
+    }
+}
+
+/// Error returned when replaying a trace.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The trace has no more entries.
+    Exhausted,
+    /// The transaction does not match the next entry of the trace.
+    Mismatch,
+    /// The transaction failed with an error of the given kind when the trace was recorded.
+    Recorded(ErrorKind),
+}
+
+impl Error for ReplayError {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            ReplayError::Recorded(kind) => *kind,
+            ReplayError::Exhausted | ReplayError::Mismatch => ErrorKind::Other,
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ReplayError {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            ReplayError::Exhausted => defmt::write!(f, "Exhausted"),
+            ReplayError::Mismatch => defmt::write!(f, "Mismatch"),
+            ReplayError::Recorded(kind) => {
+                defmt::write!(f, "Recorded({})", defmt::Debug2Format(kind))
+            }
+        }
+    }
+}
+
+/// An I2C implementation playing back a trace captured by a [`Recorder`].
+///
+/// Every transaction is checked against the next entry of the trace; reads are served
+/// from the recorded data.
+pub struct Replayer<'b> {
+    trace: &'b [u8],
+    pos: usize,
+}
+
+impl<'b> Replayer<'b> {
+    /// Create a replayer for the given trace.
+    pub fn new(trace: &'b [u8]) -> Self {
+        Self { trace, pos: 0 }
+    }
+
+    /// Whether all entries of the trace have been played back.
+    pub fn done(&self) -> bool {
+        self.pos >= self.trace.len()
+    }
+
+    fn next_byte(&mut self) -> Result<u8, ReplayError> {
+        let byte = *self.trace.get(self.pos).ok_or(ReplayError::Mismatch)?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn next_payload(&mut self) -> Result<&'b [u8], ReplayError> {
+        let len = self.next_byte()? as usize;
+        let payload = self
+            .trace
+            .get(self.pos..self.pos + len)
+            .ok_or(ReplayError::Mismatch)?;
+        self.pos += len;
+        Ok(payload)
+    }
+
+    fn expect(&mut self, tag: u8, address: u8) -> Result<(), ReplayError> {
+        if self.done() {
+            return Err(ReplayError::Exhausted);
+        }
+        let recorded_tag = self.next_byte()?;
+        let recorded_address = self.next_byte()?;
+        if recorded_address != address {
+            Err(ReplayError::Mismatch)
+        } else if recorded_tag == TAG_ERROR {
+            let kind = self.next_payload()?.first().copied().unwrap_or(0);
+            Err(ReplayError::Recorded(decode_kind(kind)))
+        } else if recorded_tag != tag {
+            Err(ReplayError::Mismatch)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn replay_write(&mut self, write: &[u8]) -> Result<(), ReplayError> {
+        if self.next_payload()? == write {
+            Ok(())
+        } else {
+            Err(ReplayError::Mismatch)
+        }
+    }
+
+    fn replay_read(&mut self, read: &mut [u8]) -> Result<(), ReplayError> {
+        let payload = self.next_payload()?;
+        if payload.len() == read.len() {
+            read.copy_from_slice(payload);
+            Ok(())
+        } else {
+            Err(ReplayError::Mismatch)
+        }
+    }
+}
+
+impl<'b> ErrorType for Replayer<'b> {
+    type Error = ReplayError;
+}
+
+impl<'b> I2c<SevenBitAddress> for Replayer<'b> {
+    async fn read(&mut self, address: u8, read: &mut [u8]) -> Result<(), Self::Error> {
+        self.expect(TAG_READ, address)?;
+        self.replay_read(read)
+    }
+
+    async fn write(&mut self, address: u8, write: &[u8]) -> Result<(), Self::Error> {
+        self.expect(TAG_WRITE, address)?;
+        self.replay_write(write)
+    }
+
+    async fn write_read(
+        &mut self,
+        address: u8,
+        write: &[u8],
+        read: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.expect(TAG_WRITE_READ, address)?;
+        self.replay_write(write)?;
+        self.replay_read(read)
+    }
+
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        for operation in operations.iter_mut() {
+            match operation {
+                Operation::Read(read) => {
+                    self.expect(TAG_READ, address)?;
+                    self.replay_read(read)?;
+                }
+                Operation::Write(write) => {
+                    self.expect(TAG_WRITE, address)?;
+                    self.replay_write(write)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::*;
+
+    #[test]
+    fn replays_error_kind() {
+        let kinds = [
+            ErrorKind::Bus,
+            ErrorKind::ArbitrationLoss,
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address),
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data),
+            ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown),
+            ErrorKind::Overrun,
+            ErrorKind::Other,
+        ];
+        for kind in kinds {
+            let mut buf = [0; 16];
+            let mut sensor = FakeSensor::new();
+            sensor.fail = 1;
+            sensor.error = kind;
+            let mut recorder = Recorder::new(sensor, &mut buf);
+            let mut read = [0; 1];
+            assert!(block_on(recorder.write_read(0x5F, &[WHO_AM_I], &mut read)).is_err());
+            assert!(block_on(recorder.write_read(0x5F, &[WHO_AM_I], &mut read)).is_ok());
+
+            let (_, trace) = recorder.release();
+            let mut replayer = Replayer::new(trace);
+            let error = block_on(replayer.write_read(0x5F, &[WHO_AM_I], &mut read)).unwrap_err();
+            assert_eq!(error, ReplayError::Recorded(kind));
+            assert_eq!(error.kind(), kind);
+            assert!(block_on(replayer.write_read(0x5F, &[WHO_AM_I], &mut read)).is_ok());
+            assert_eq!(read, [0xBC]);
+            assert!(replayer.done());
+        }
+    }
+
+    #[test]
+    fn replays_empty_error_as_other() {
+        let trace = [TAG_ERROR, 0x5F, 0];
+        let mut replayer = Replayer::new(&trace);
+        let error = block_on(replayer.write(0x5F, &[0x20, 0])).unwrap_err();
+        assert_eq!(error, ReplayError::Recorded(ErrorKind::Other));
+    }
+}